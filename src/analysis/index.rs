@@ -0,0 +1,213 @@
+use std::cmp::Reverse;
+use crate::{
+    common::span::Span,
+    parser::ast::{
+        ClassDecl,
+        EnumDecl,
+        Expr,
+        ImportDecl,
+        Imported,
+        MatchArm,
+        NodeId,
+        Pattern,
+        Stmt,
+        StmtKind,
+        TypeExpr,
+        Visitor,
+    },
+};
+
+/// What sort of node an id belongs to
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NodeKind {
+    Stmt,
+    Expr,
+    Type,
+    Pattern,
+    MatchArm,
+    Field,
+    TypeParam,
+    Constraint,
+    Variant,
+    ImportItem,
+}
+
+#[derive(Debug)]
+pub struct NodeEntry {
+    pub kind: NodeKind,
+    pub span: Span,
+    pub parent: Option<NodeId>,
+
+    /// How many nodes enclose this one, top-level statements are at depth 0
+    pub depth: usize,
+}
+
+/// Side table from node ids back to the tree, built once after parsing. Entries
+/// are stored in a vector indexed by the id, with holes for unused ids.
+#[derive(Debug)]
+pub struct NodeIndex {
+    entries: Vec<Option<NodeEntry>>,
+}
+
+impl NodeIndex {
+    pub fn build(ast: &[Stmt]) -> NodeIndex {
+        let mut builder = Builder { entries: vec![], parents: vec![] };
+        builder.visit_block(ast);
+        return NodeIndex { entries: builder.entries };
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&NodeEntry> {
+        return self.entries.get(id.index()).and_then(Option::as_ref);
+    }
+
+    pub fn kind(&self, id: NodeId) -> Option<NodeKind> {
+        return self.get(id).map(|entry| entry.kind);
+    }
+
+    pub fn span(&self, id: NodeId) -> Option<&Span> {
+        return self.get(id).map(|entry| &entry.span);
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        return self.get(id).and_then(|entry| entry.parent);
+    }
+
+    /// The parent of `id`, its parent and so on up to the top-level statement
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        return std::iter::successors(self.parent(id), |&id| self.parent(id));
+    }
+
+    /// Every indexed node, in id order
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &NodeEntry)> {
+        return self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| entry.as_ref().map(|entry| (NodeId(i), entry)));
+    }
+
+    /// The innermost node whose span covers the byte at `offset`. A node and its
+    /// child can share a span, like `(a)` and `a`, in which case the child wins.
+    pub fn node_at(&self, offset: usize) -> Option<NodeId> {
+        return self
+            .iter()
+            .filter(|(_, entry)| entry.span.contains(&offset))
+            .min_by_key(|(_, entry)| (entry.span.len(), Reverse(entry.depth)))
+            .map(|(id, _)| id);
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// BUILDING
+// ----------------------------------------------------------------- \\
+
+struct Builder {
+    entries: Vec<Option<NodeEntry>>,
+
+    /// The nodes enclosing the one being visited, innermost last
+    parents: Vec<NodeId>,
+}
+
+impl Builder {
+    fn enter(&mut self, id: NodeId, span: &Span, kind: NodeKind) {
+        if self.entries.len() <= id.index() {
+            self.entries.resize_with(id.index() + 1, || None);
+        }
+        self.entries[id.index()] = Some(NodeEntry {
+            kind,
+            span: span.clone(),
+            parent: self.parents.last().copied(),
+            depth: self.parents.len(),
+        });
+        self.parents.push(id);
+    }
+
+    fn leave(&mut self) {
+        self.parents.pop();
+    }
+
+    /// Records a node that has no children of its own
+    fn leaf(&mut self, id: NodeId, span: &Span, kind: NodeKind) {
+        self.enter(id, span, kind);
+        self.leave();
+    }
+}
+
+impl Visitor for Builder {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.enter(stmt.uid, &stmt.span, NodeKind::Stmt);
+
+        // the walker skips these since they hold no expressions, but they have ids
+        if let StmtKind::Function { type_params, where_clause, .. } = &stmt.kind {
+            for param in type_params {
+                self.leaf(param.uid, &param.span, NodeKind::TypeParam);
+            }
+            for constraint in where_clause {
+                self.leaf(constraint.uid, &constraint.span, NodeKind::Constraint);
+            }
+        }
+        self.walk_stmt(stmt);
+        self.leave();
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.enter(expr.uid, &expr.span, NodeKind::Expr);
+        self.walk_expr(expr);
+        self.leave();
+    }
+
+    fn visit_type(&mut self, ty: &TypeExpr) {
+        self.enter(ty.uid, &ty.span, NodeKind::Type);
+        self.walk_type(ty);
+        self.leave();
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        self.enter(pattern.uid, &pattern.span, NodeKind::Pattern);
+        self.walk_pattern(pattern);
+        self.leave();
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        self.enter(arm.uid, &arm.span, NodeKind::MatchArm);
+        self.walk_match_arm(arm);
+        self.leave();
+    }
+
+    fn visit_class(&mut self, decl: &ClassDecl) {
+        for param in &decl.type_params {
+            self.leaf(param.uid, &param.span, NodeKind::TypeParam);
+        }
+        for constraint in &decl.where_clause {
+            self.leaf(constraint.uid, &constraint.span, NodeKind::Constraint);
+        }
+        for field in &decl.fields {
+            self.enter(field.uid, &field.span, NodeKind::Field);
+            self.visit_type(&field.ty);
+            self.leave();
+        }
+        if let Some(constructor) = &decl.constructor {
+            self.visit_stmt(constructor);
+        }
+        for method in &decl.methods {
+            self.visit_stmt(method);
+        }
+    }
+
+    fn visit_enum(&mut self, decl: &EnumDecl) {
+        for variant in &decl.variants {
+            self.enter(variant.uid, &variant.span, NodeKind::Variant);
+            for ty in &variant.payload {
+                self.visit_type(ty);
+            }
+            self.leave();
+        }
+    }
+
+    fn visit_import(&mut self, decl: &ImportDecl) {
+        if let Imported::Items(items) = &decl.imported {
+            for item in items {
+                self.leaf(item.uid, &item.span, NodeKind::ImportItem);
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{
+    analysis::index::NodeIndex,
+    common::{ errors::{ Error, ErrorBuffer, ErrorKind, ErrorWriter }, meta::Type, span::Span },
+    emit::printer::print_type,
+    parser::ast::{ ClassDecl, Expr, ExprKind, FieldDecl, NodeId, Stmt, StmtKind, TypeExpr, TypeKind, VisitorMut },
+    throw,
+};
+
+/// Checks where `let self.x = ...` and `default` are used, and replaces every
+/// `default` with the zero value of the type it initializes. The new nodes get
+/// fresh ids past the ones the parser handed out, except the top one which keeps
+/// the id of the `default` it stands in for.
+pub fn resolve(ast: &mut Vec<Stmt>) -> ErrorBuffer {
+    let next = NodeIndex::build(ast).iter().last().map_or(0, |(id, _)| id.index() + 1);
+    let mut resolver = Resolver { next, fields: None, errors: vec![] };
+    resolver.visit_block(ast);
+    return resolver.dump_errors();
+}
+
+struct Resolver {
+    /// The next unused node id
+    next: usize,
+
+    /// The fields of the class whose constructor is being walked, if any
+    fields: Option<Vec<FieldDecl>>,
+    errors: ErrorBuffer,
+}
+
+impl ErrorWriter for Resolver {
+    fn error(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    fn dump_errors(&mut self) -> ErrorBuffer {
+        return self.errors.drain(0..).collect();
+    }
+}
+
+impl Resolver {
+    fn id(&mut self) -> NodeId {
+        self.next += 1;
+        return NodeId(self.next - 1);
+    }
+
+    /// Swaps a `default` for the zero value of `ty`, or for an error node if the
+    /// type doesn't have one
+    fn replace(&mut self, value: &mut Expr, ty: &TypeExpr) {
+        match self.zero(ty, &value.span) {
+            Ok(zero) => value.kind = zero,
+            Err(msg) => {
+                self.error(throw!(TypeMismatch, value.span.clone(), msg));
+                value.kind = ExprKind::Error;
+            }
+        }
+    }
+
+    /// The zero value of a type as an expression, spanning the `default` it replaces
+    fn zero(&mut self, ty: &TypeExpr, span: &Span) -> Result<ExprKind, String> {
+        let kind = match &ty.kind {
+            TypeKind::Named { name } => {
+                match Type::get_primitive_from_ident(name) {
+                    Some(Type::Int) => ExprKind::Integer { value: 0 },
+                    Some(Type::Float) => ExprKind::Float { value: 0.0 },
+                    Some(Type::Str) => ExprKind::String { value: String::new() },
+                    Some(Type::Bool) => ExprKind::Boolean { value: false },
+                    Some(Type::None) => ExprKind::Ident { name: "None".to_string() },
+                    _ => return Err(format!("'{}' has no default value", print_type(ty))),
+                }
+            }
+            TypeKind::Nullable { .. } => ExprKind::Ident { name: "None".to_string() },
+            TypeKind::Array { elem, size } => {
+                let ExprKind::Integer { value } = size.kind else {
+                    let msg = format!("the size of '{}' must be a number for it to have a default value", print_type(ty));
+                    return Err(msg);
+                };
+                let elem = self.zero_expr(elem, span)?;
+                let count = Expr::new(self.id(), ExprKind::Integer { value }, span.clone());
+                ExprKind::ArrayRepeat { value: Box::new(elem), count: Box::new(count) }
+            }
+            TypeKind::Sequence { .. } => ExprKind::SeqLiteral { elements: vec![] },
+            TypeKind::Tuple { elems } => {
+                let elements = elems
+                    .iter()
+                    .map(|elem| self.zero_expr(elem, span))
+                    .collect::<Result<Vec<Expr>, String>>()?;
+                ExprKind::Tuple { elements }
+            }
+            TypeKind::Generic { .. } | TypeKind::Function { .. } => {
+                return Err(format!("'{}' has no default value", print_type(ty)));
+            }
+            TypeKind::Error => ExprKind::Error,
+        };
+        return Ok(kind);
+    }
+
+    fn zero_expr(&mut self, ty: &TypeExpr, span: &Span) -> Result<Expr, String> {
+        let kind = self.zero(ty, span)?;
+        return Ok(Expr::new(self.id(), kind, span.clone()));
+    }
+
+    /// Walks something with its own body, where fields can't be initialized
+    fn outside_constructor(&mut self, walk: impl FnOnce(&mut Resolver)) {
+        let fields = self.fields.take();
+        walk(self);
+        self.fields = fields;
+    }
+}
+
+impl VisitorMut for Resolver {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Variable { typ, value, .. } if matches!(value.kind, ExprKind::Default) => {
+                match typ {
+                    Some(ty) => self.replace(value, ty),
+                    None => {
+                        let msg = "'default' needs a type annotation to know what value to take";
+                        self.error(throw!(TypeMismatch, value.span.clone(), msg.to_string()));
+                        value.kind = ExprKind::Error;
+                    }
+                }
+            }
+            StmtKind::FieldInit { name, value } => {
+                let Some(fields) = self.fields.take() else {
+                    let msg = format!("'let self.{name}' can only be used inside a constructor");
+                    self.error(throw!(SyntaxError, stmt.span.clone(), msg));
+                    return self.walk_stmt(stmt);
+                };
+
+                match fields.iter().find(|field| &field.name == name) {
+                    Some(field) if matches!(value.kind, ExprKind::Default) => self.replace(value, &field.ty),
+                    Some(_) => {}
+                    None => {
+                        let msg = format!("'{name}' is not a field of this class");
+                        self.error(throw!(UnknownIdentifier, stmt.span.clone(), msg));
+                    }
+                }
+                self.fields = Some(fields);
+            }
+            StmtKind::Function { .. } => {
+                return self.outside_constructor(|resolver| resolver.walk_stmt(stmt));
+            }
+            _ => {}
+        }
+        self.walk_stmt(stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &expr.kind {
+            ExprKind::Default => {
+                let msg = "'default' can only be the value of an annotated 'let' or a field initializer";
+                self.error(throw!(SyntaxError, expr.span.clone(), msg.to_string()));
+                expr.kind = ExprKind::Error;
+            }
+            ExprKind::Lambda { .. } => self.outside_constructor(|resolver| resolver.walk_expr(expr)),
+            _ => self.walk_expr(expr),
+        }
+    }
+
+    /// Same as the default walk, except that the constructor body gets to see the
+    /// fields it initializes
+    fn visit_class(&mut self, decl: &mut ClassDecl) {
+        for field in &mut decl.fields {
+            self.visit_type(&mut field.ty);
+        }
+
+        let outer = self.fields.replace(std::mem::take(&mut decl.fields));
+        if let Some(constructor) = &mut decl.constructor {
+            self.walk_stmt(constructor);
+        }
+        decl.fields = std::mem::replace(&mut self.fields, outer).unwrap_or_default();
+
+        for method in &mut decl.methods {
+            self.visit_stmt(method);
+        }
+    }
+}
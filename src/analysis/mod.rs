@@ -1 +1,4 @@
 pub mod analyzer;
+pub mod index;
+pub mod initializers;
+pub mod variants;
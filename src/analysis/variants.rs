@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use crate::{
+    common::{
+        errors::{ Error, ErrorBuffer, ErrorKind, ErrorWriter },
+        meta::{ SymbolInfo, SymbolKind, TypeDecl },
+        span::Span,
+    },
+    parser::ast::{
+        ClassDecl,
+        Expr,
+        ExprKind,
+        MatchArm,
+        Pattern,
+        PatternKind,
+        Stmt,
+        StmtKind,
+        TypeExpr,
+        VisitorMut,
+    },
+    throw,
+};
+
+/// Turns `Name.Variant` and `Name.Variant(args)` into enum values wherever `Name`
+/// is an enum in scope. The parser can't know what a name refers to, so it reads
+/// these as a field access and a method call, which is what they stay when `Name`
+/// is a local that shadows the enum or isn't known to be an enum at all.
+pub fn resolve(ast: &mut Vec<Stmt>) -> ErrorBuffer {
+    let mut resolver = Resolver { scopes: vec![HashMap::new()], errors: vec![] };
+
+    // top-level declarations can be used before they appear
+    for stmt in ast.iter() {
+        match &stmt.kind {
+            StmtKind::Enum(decl) => {
+                let symbol = SymbolInfo::from_enum(decl, stmt.uid);
+                resolver.declare(&decl.name, symbol.kind);
+            }
+            StmtKind::Class(decl) => resolver.declare(&decl.name, SymbolKind::Type(TypeDecl::Class)),
+            StmtKind::Function { name, .. } => resolver.declare(name, SymbolKind::Function),
+            _ => {}
+        }
+    }
+
+    resolver.walk_block(ast);
+    return resolver.dump_errors();
+}
+
+struct Resolver {
+    /// What each name in scope stands for, innermost scope last
+    scopes: Vec<HashMap<String, SymbolKind>>,
+    errors: ErrorBuffer,
+}
+
+impl ErrorWriter for Resolver {
+    fn error(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    fn dump_errors(&mut self) -> ErrorBuffer {
+        return self.errors.drain(0..).collect();
+    }
+}
+
+impl Resolver {
+    fn declare(&mut self, name: &str, kind: SymbolKind) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), kind);
+    }
+
+    /// Declares every name bound by a pattern as a variable
+    fn bind(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Binding { name } => self.declare(name, SymbolKind::Variable),
+            PatternKind::Tuple { elements: fields } | PatternKind::Variant { fields, .. } => {
+                fields.iter().for_each(|field| self.bind(field));
+            }
+            _ => {}
+        }
+    }
+
+    /// Walks a function or lambda, with its parameters in scope of the body
+    fn function(&mut self, params: &mut [Expr], ret: Option<&mut TypeExpr>, body: &mut Vec<Stmt>) {
+        self.scoped(|resolver| {
+            for param in params {
+                if let ExprKind::Parameter { name, .. } = &param.kind {
+                    resolver.declare(name, SymbolKind::Variable);
+                }
+                resolver.visit_expr(param);
+            }
+            if let Some(ret) = ret {
+                resolver.visit_type(ret);
+            }
+            resolver.walk_block(body);
+        });
+    }
+
+    /// Runs `walk` in a scope of its own
+    fn scoped(&mut self, walk: impl FnOnce(&mut Resolver)) {
+        self.scopes.push(HashMap::new());
+        walk(self);
+        self.scopes.pop();
+    }
+
+    /// The variants of `name`, if it is an enum in scope
+    fn variants(&self, name: &str) -> Option<&Vec<String>> {
+        return match self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            Some(SymbolKind::Type(TypeDecl::Enum { variants })) => Some(variants),
+            _ => None,
+        };
+    }
+
+    /// Reports `Name.variant` if `Name` is an enum without that variant
+    fn check_variant(&mut self, enum_name: &str, variant: &str, span: &Span) {
+        if self.variants(enum_name).is_some_and(|variants| !variants.iter().any(|v| v == variant)) {
+            let msg = format!("'{enum_name}' has no variant '{variant}'");
+            self.error(throw!(UnknownIdentifier, span.clone(), msg));
+        }
+    }
+
+    /// The enum and variant named by `object.name`, if `object` is an enum in scope
+    fn variant_of(&self, object: &Expr, name: &str) -> Option<(String, String)> {
+        let ExprKind::Ident { name: enum_name } = &object.kind else {
+            return None;
+        };
+        self.variants(enum_name)?;
+        return Some((enum_name.clone(), name.to_string()));
+    }
+}
+
+impl VisitorMut for Resolver {
+    fn visit_block(&mut self, stmts: &mut Vec<Stmt>) {
+        self.scoped(|resolver| resolver.walk_block(stmts));
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Variable { pattern, typ, value, .. } => {
+                if let Some(typ) = typ {
+                    self.visit_type(typ);
+                }
+                self.visit_expr(value);
+                self.bind(pattern);
+            }
+            StmtKind::Function { name, params, ret, body, .. } => {
+                self.declare(name, SymbolKind::Function);
+                self.function(params, ret.as_mut(), body);
+            }
+            StmtKind::For { binding, iterable, body } => {
+                self.visit_expr(iterable);
+                self.scoped(|resolver| {
+                    resolver.bind(binding);
+                    resolver.walk_block(body);
+                });
+            }
+            _ => self.walk_stmt(stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        let variant = match &expr.kind {
+            ExprKind::Field { object, name } => self.variant_of(object, name),
+            ExprKind::Call { callee, .. } => {
+                match &callee.kind {
+                    ExprKind::Field { object, name } => self.variant_of(object, name),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some((enum_name, variant)) = variant {
+            self.check_variant(&enum_name, &variant, &expr.span);
+            let args = match std::mem::replace(&mut expr.kind, ExprKind::Error) {
+                ExprKind::Call { args, .. } => args,
+                _ => vec![],
+            };
+            expr.kind = ExprKind::Variant { enum_name, variant, args };
+        }
+
+        match &mut expr.kind {
+            ExprKind::Lambda { params, ret, body } => self.function(params, ret.as_mut(), body),
+            _ => self.walk_expr(expr),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        if let PatternKind::Variant { enum_name, variant, .. } = &pattern.kind {
+            let (enum_name, variant) = (enum_name.clone(), variant.clone());
+            self.check_variant(&enum_name, &variant, &pattern.span);
+        }
+        self.walk_pattern(pattern);
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        self.scoped(|resolver| {
+            resolver.visit_pattern(&mut arm.pattern);
+            resolver.bind(&arm.pattern);
+            if let Some(guard) = &mut arm.guard {
+                resolver.visit_expr(guard);
+            }
+            resolver.walk_block(&mut arm.body);
+        });
+    }
+
+    /// Methods are declared in a scope of the class, not next to it
+    fn visit_class(&mut self, decl: &mut ClassDecl) {
+        self.scoped(|resolver| resolver.walk_class(decl));
+    }
+}
@@ -1,5 +1,5 @@
 use std::fmt::Display;
-use super::span::{ formatted_content, line_number, Span };
+use super::span::Span;
 
 // ----------------------------------------------------------------- \\
 // MACROS
@@ -30,6 +30,8 @@ pub trait ErrorWriter {
 // ERROR STRUCT
 // ----------------------------------------------------------------- \\
 
+// only printed with Debug until error reporting is wired up
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct Error {
     /// Refers to the kind of error and may or may not have
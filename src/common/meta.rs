@@ -1,6 +1,5 @@
 use std::{ collections::HashMap, fmt::Display };
-use crate::parser::ast::{ Expr, Operator };
-use super::errors::Error;
+use crate::parser::ast::{ EnumDecl, MatchArm, NodeId, PatternKind };
 
 // ----------------------------------------------------------------- \\
 // TYPING STRUCTURES
@@ -15,6 +14,7 @@ pub enum Type {
     Str,
     Bool,
     Function(Vec<Type>, Box<Type>),
+    Enum(String),
     None,
 }
 
@@ -26,14 +26,15 @@ impl Display for Type {
             Self::Str => write!(f, "str"),
             Self::Bool => write!(f, "bool"),
             Self::Function(pars, ret) => write!(f, "Func ({pars:?}) -> {ret}"),
+            Self::Enum(name) => write!(f, "{name}"),
             Self::None => write!(f, "none"),
         }
     }
 }
 
 impl Type {
-    pub fn get_primitive_from_ident(id: &String) -> Option<Type> {
-        match id.as_str() {
+    pub fn get_primitive_from_ident(id: &str) -> Option<Type> {
+        match id {
             "int" => Some(Type::Int),
             "float" => Some(Type::Float),
             "str" => Some(Type::Str),
@@ -51,10 +52,10 @@ impl Type {
 /// High level metadata structure that incorporates all aspects of semantic
 /// analysis in the program into one structure.
 pub struct Metadata {
-    types: HashMap<usize, Type>,
-    symbols: HashMap<usize, SymbolInfo>,
-    scopes: HashMap<usize, ScopeInfo>,
-    // overloads: HashMap<usize, OverloadInfo>,
+    types: HashMap<NodeId, Type>,
+    symbols: HashMap<NodeId, SymbolInfo>,
+    scopes: HashMap<NodeId, ScopeInfo>,
+    // overloads: HashMap<NodeId, OverloadInfo>,
 }
 
 // ----------------------------------------------------------------- \\
@@ -62,16 +63,71 @@ pub struct Metadata {
 // ----------------------------------------------------------------- \\
 
 pub struct SymbolInfo {
-    name: String,
-    kind: SymbolKind,
-    node: usize,
-    ty: Type,
+    pub name: String,
+    pub kind: SymbolKind,
+    pub node: NodeId,
+    pub ty: Type,
+}
+
+impl SymbolInfo {
+    /// Creates the type symbol introduced by an enum declaration, `node` being
+    /// the UID of the declaring statement
+    pub fn from_enum(decl: &EnumDecl, node: NodeId) -> SymbolInfo {
+        let variants = decl.variants
+            .iter()
+            .map(|variant| variant.name.clone())
+            .collect();
+
+        return SymbolInfo {
+            name: decl.name.clone(),
+            kind: SymbolKind::Type(TypeDecl::Enum { variants }),
+            node,
+            ty: Type::Enum(decl.name.clone()),
+        };
+    }
 }
 
 pub enum SymbolKind {
     Variable,
     Function,
-    Type,
+    Type(TypeDecl),
+}
+
+/// The kind of declaration that introduced a type symbol
+pub enum TypeDecl {
+    Class,
+    Enum {
+        variants: Vec<String>,
+    },
+}
+
+impl TypeDecl {
+    /// Gives back the variants of the enum `name` that none of the arms of a match
+    /// are sure to cover, guarded arms don't count since they can still fall through
+    pub fn missing_variants(&self, name: &str, arms: &[MatchArm]) -> Vec<String> {
+        let TypeDecl::Enum { variants } = self else {
+            return vec![];
+        };
+        if arms.iter().any(|arm| arm.is_catch_all()) {
+            return vec![];
+        }
+
+        return variants
+            .iter()
+            .filter(|missing| {
+                !arms.iter().any(|arm| {
+                    let PatternKind::Variant { enum_name, variant, fields } = &arm.pattern.kind else {
+                        return false;
+                    };
+                    arm.guard.is_none() &&
+                        enum_name == name &&
+                        variant == *missing &&
+                        fields.iter().all(|field| field.is_irrefutable())
+                })
+            })
+            .cloned()
+            .collect();
+    }
 }
 
 // ----------------------------------------------------------------- \\
@@ -79,7 +135,8 @@ pub enum SymbolKind {
 // ----------------------------------------------------------------- \\
 
 pub struct ScopeInfo {
-    id: usize,
-    parent: Option<usize>,
+    /// The node that opens the scope, like a function or block statement
+    id: NodeId,
+    parent: Option<NodeId>,
     symbols: HashMap<String, SymbolInfo>
 }
\ No newline at end of file
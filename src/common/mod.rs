@@ -1,3 +1,6 @@
 pub mod errors;
 pub mod span;
-pub mod meta;
\ No newline at end of file
+// semantic analysis scaffolding, not read by anything yet
+#[allow(dead_code)]
+pub mod meta;
+pub mod trace;
@@ -1,16 +1,14 @@
 use std::ops::Range;
 pub type Span = Range<usize>;
 
-pub fn line_number(span: &Span, source: &String) -> usize {
-    return (
-        source[..span.start]
-            .chars()
-            .filter(|&c| c == '\n')
-            .count() + 1
-    );
+pub fn line_number(span: &Span, source: &str) -> usize {
+    return source[..span.start]
+        .chars()
+        .filter(|&c| c == '\n')
+        .count() + 1;
 }
 
-pub fn formatted_content(span: &Span, underline: &Span, source: &String) -> Option<String> {
+pub fn formatted_content(span: &Span, underline: &Span, source: &str) -> Option<String> {
     if span.end > source.len() || span.start > span.end {
         return None;
     }
@@ -0,0 +1,56 @@
+use std::{ cell::RefCell, fs::File, io::{ self, BufWriter, Write }, rc::Rc };
+use crate::lexer::token::Token;
+
+/// Environment variable that turns tracing on like `--trace` does, set to `1` or
+/// `stderr` for stderr or to the path of the file to write to. Empty or `0` is off.
+pub const TRACE_VAR: &str = "KASKA_TRACE";
+
+/// Writes a line for every token the lexer produces and for every rule the parser
+/// nests into as it enters and leaves it, which are statements, class members,
+/// operands, types and patterns, for debugging the front end. Clones share the
+/// output, so the lexer and parser write one trace between them.
+#[derive(Clone)]
+pub struct Tracer {
+    out: Rc<RefCell<Box<dyn Write>>>,
+}
+
+impl Tracer {
+    pub fn new(out: impl Write + 'static) -> Tracer {
+        return Tracer { out: Rc::new(RefCell::new(Box::new(out))) };
+    }
+
+    /// Opens the trace named by a `--trace` value or `KASKA_TRACE`, either stderr
+    /// or a file that is created or truncated, or none at all for empty or `0`
+    pub fn open(target: &str) -> io::Result<Option<Tracer>> {
+        return match target {
+            "" | "0" => Ok(None),
+            "1" | "stderr" => Ok(Some(Tracer::new(io::stderr()))),
+            path => Ok(Some(Tracer::new(BufWriter::new(File::create(path)?)))),
+        };
+    }
+
+    pub fn token(&self, token: &Token) {
+        self.line(format!("token {}", position(token)));
+    }
+
+    /// Records the parser entering `rule` on `token`, `depth` rules deep
+    pub fn enter(&self, rule: &str, depth: usize, token: &Token) {
+        self.line(format!("{:width$}> {rule} (depth {depth}) at {}", "", position(token), width = depth * 2));
+    }
+
+    /// Records the parser leaving `rule` on `token`, and whether it parsed
+    pub fn exit(&self, rule: &str, depth: usize, token: &Token, ok: bool) {
+        let outcome = if ok { "ok" } else { "failed" };
+        self.line(format!("{:width$}< {rule} (depth {depth}) at {} {outcome}", "", position(token), width = depth * 2));
+    }
+
+    /// A trace is only for reading along, so failing to write it is not an error
+    fn line(&self, line: String) {
+        let _ = writeln!(self.out.borrow_mut(), "{line}");
+    }
+}
+
+/// Where a token is and what it is, like `12..15 Ident "foo"`
+fn position(token: &Token) -> String {
+    return format!("{}..{} {:?} {:?}", token.span.start, token.span.end, token.kind, token.lexeme);
+}
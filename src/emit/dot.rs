@@ -0,0 +1,95 @@
+use crate::{ emit::node::{ self, Node, Value }, parser::ast::Stmt };
+
+/// The tree as a Graphviz DOT graph. Each node is labeled with its kind, its
+/// scalar fields and its span, and points to its children through edges named
+/// after the field holding them.
+pub fn ast(stmts: &[Stmt]) -> String {
+    let mut graph = Graph { out: String::new(), next: 0 };
+    graph.out.push_str("digraph ast {\n");
+    graph.out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+
+    let root = graph.add("program".to_string());
+    for (i, stmt) in stmts.iter().enumerate() {
+        graph.child(root, &format!("[{i}]"), &node::stmt(stmt));
+    }
+
+    graph.out.push_str("}\n");
+    return graph.out;
+}
+
+struct Graph {
+    out: String,
+    next: usize,
+}
+
+impl Graph {
+    /// Adds a node with the given label and returns its id
+    fn add(&mut self, label: String) -> usize {
+        let id = self.next;
+        self.next += 1;
+        self.out.push_str(&format!("    n{id} [label=\"{}\"];\n", escape(&label)));
+        return id;
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str) {
+        self.out.push_str(&format!("    n{from} -> n{to} [label=\"{}\"];\n", escape(label)));
+    }
+
+    /// Links whatever `value` holds under `parent`, lists get one edge per item
+    fn child(&mut self, parent: usize, label: &str, value: &Value) {
+        match value {
+            Value::Node(node) => {
+                let id = self.node(node);
+                self.edge(parent, id, label);
+            }
+            Value::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.child(parent, &format!("{label}[{i}]"), item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn node(&mut self, node: &Node) -> usize {
+        let mut label = node.kind.to_owned();
+        for (name, value) in &node.fields {
+            if let Some(scalar) = scalar(value) {
+                label.push_str(&format!("\n{name}: {scalar}"));
+            }
+        }
+        if let Some(span) = &node.span {
+            label.push_str(&format!("\n{}..{}", span.start, span.end));
+        }
+
+        let id = self.add(label);
+        for (name, value) in &node.fields {
+            self.child(id, name, value);
+        }
+        return id;
+    }
+}
+
+/// The text of a field that goes into its node's label instead of getting a node of
+/// its own. Lists of names like modifiers and import paths count as well.
+fn scalar(value: &Value) -> Option<String> {
+    return match value {
+        Value::Null | Value::Node(_) => None,
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Int(value) => Some(value.to_string()),
+        Value::Float(value) => Some(format!("{value:?}")),
+        Value::Str(value) => Some(format!("{value:?}")),
+        Value::Name(value) => Some(value.to_owned()),
+        Value::List(items) => {
+            let names: Option<Vec<String>> = items
+                .iter()
+                .map(|item| if let Value::Name(name) = item { Some(name.to_owned()) } else { None })
+                .collect();
+            names.filter(|names| !names.is_empty()).map(|names| names.join(" "))
+        }
+    };
+}
+
+fn escape(label: &str) -> String {
+    return label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+}
@@ -0,0 +1,99 @@
+use crate::{ emit::node::{ self, Node, Value }, lexer::token::Token, parser::ast::Stmt };
+
+/// The tokens as a JSON array, one object per token with its kind, span and lexeme
+pub fn tokens(tokens: &[Token]) -> String {
+    return json(&node::tokens(tokens));
+}
+
+/// The tree as a JSON array of statements. Every node is an object holding its
+/// `kind`, `span` and `uid` followed by the fields of the AST node, in order.
+pub fn ast(stmts: &[Stmt]) -> String {
+    return json(&node::block(stmts));
+}
+
+pub fn json(value: &Value) -> String {
+    let mut out = String::new();
+    write(&mut out, value, 0);
+    out.push('\n');
+    return out;
+}
+
+fn newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(value) => out.push_str(&value.to_string()),
+        Value::Int(value) => out.push_str(&value.to_string()),
+        // JSON has no way to spell infinities, which is what overlong float literals become
+        Value::Float(value) if value.is_finite() => out.push_str(&format!("{value:?}")),
+        Value::Float(_) => out.push_str("null"),
+        Value::Str(value) | Value::Name(value) => string(out, value),
+        Value::List(items) if items.is_empty() => out.push_str("[]"),
+        Value::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, indent + 1);
+                write(out, item, indent + 1);
+            }
+            newline(out, indent);
+            out.push(']');
+        }
+        Value::Node(node) => object(out, node, indent),
+    }
+}
+
+fn object(out: &mut String, node: &Node, indent: usize) {
+    let mut first = true;
+    let mut key = |out: &mut String, key: &str| {
+        if !first {
+            out.push(',');
+        }
+        first = false;
+        newline(out, indent + 1);
+        string(out, key);
+        out.push_str(": ");
+    };
+
+    out.push('{');
+    key(out, "kind");
+    string(out, &node.kind);
+    if let Some(span) = &node.span {
+        key(out, "span");
+        out.push_str(&format!("[{}, {}]", span.start, span.end));
+    }
+    if let Some(uid) = node.uid {
+        key(out, "uid");
+        out.push_str(&uid.index().to_string());
+    }
+    for (name, value) in &node.fields {
+        key(out, name);
+        write(out, value, indent + 1);
+    }
+    newline(out, indent);
+    out.push('}');
+}
+
+fn string(out: &mut String, value: &str) {
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
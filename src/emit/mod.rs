@@ -0,0 +1,5 @@
+pub mod dot;
+pub mod json;
+pub mod node;
+pub mod printer;
+pub mod sexp;
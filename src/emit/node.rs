@@ -0,0 +1,360 @@
+use crate::{
+    common::span::Span,
+    lexer::token::Token,
+    parser::ast::{
+        ClassDecl,
+        Constraint,
+        EnumDecl,
+        Expr,
+        ExprKind,
+        FieldDecl,
+        ImportDecl,
+        ImportItem,
+        Imported,
+        MatchArm,
+        Modifier,
+        NodeId,
+        Pattern,
+        PatternKind,
+        Stmt,
+        StmtKind,
+        TypeExpr,
+        TypeKind,
+        TypeParam,
+        VariantDecl,
+    },
+};
+
+/// A serializer-neutral description of a token or syntax tree, which the JSON and
+/// S-expression emitters each render in their own way. Fields keep the order and
+/// names of the AST so the output stays stable as long as the AST does.
+#[derive(Debug)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+    Str(String),
+    /// An identifier, operator or keyword, left unquoted where the format allows it
+    Name(String),
+    List(Vec<Value>),
+    Node(Node),
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub kind: String,
+    pub span: Option<Span>,
+    pub uid: Option<NodeId>,
+    pub fields: Vec<(&'static str, Value)>,
+}
+
+impl Node {
+    fn new(kind: &str, span: &Span, uid: NodeId) -> Node {
+        Node { kind: kind.to_string(), span: Some(span.clone()), uid: Some(uid), fields: vec![] }
+    }
+
+    /// For the parts of a declaration that don't have a span or uid of their own
+    fn bare(kind: &str) -> Node {
+        Node { kind: kind.to_string(), span: None, uid: None, fields: vec![] }
+    }
+
+    fn with(mut self, name: &'static str, value: Value) -> Node {
+        self.fields.push((name, value));
+        return self;
+    }
+
+    fn done(self) -> Value {
+        return Value::Node(self);
+    }
+}
+
+fn name(name: &String) -> Value {
+    return Value::Name(name.to_owned());
+}
+
+fn optional<T>(value: &Option<T>, describe: impl Fn(&T) -> Value) -> Value {
+    return value.as_ref().map_or(Value::Null, describe);
+}
+
+fn list<T>(items: &[T], describe: impl Fn(&T) -> Value) -> Value {
+    return Value::List(items.iter().map(describe).collect());
+}
+
+fn modifiers(modifiers: &[Modifier]) -> Value {
+    return list(modifiers, |modifier| Value::Name(modifier.to_string()));
+}
+
+// ----------------------------------------------------------------- \\
+// TOKENS
+// ----------------------------------------------------------------- \\
+
+pub fn token(token: &Token) -> Value {
+    return Value::Node(Node {
+        kind: format!("{:?}", token.kind),
+        span: Some(token.span.clone()),
+        uid: None,
+        fields: vec![("lexeme", Value::Str(token.lexeme.to_owned()))],
+    });
+}
+
+pub fn tokens(tokens: &[Token]) -> Value {
+    return list(tokens, token);
+}
+
+// ----------------------------------------------------------------- \\
+// STATEMENTS
+// ----------------------------------------------------------------- \\
+
+pub fn block(stmts: &[Stmt]) -> Value {
+    return list(stmts, stmt);
+}
+
+pub fn stmt(stmt: &Stmt) -> Value {
+    let node = |kind| Node::new(kind, &stmt.span, stmt.uid);
+    return match &stmt.kind {
+        StmtKind::Variable { modifiers: mods, pattern: pat, typ, value } => {
+            node("Variable")
+                .with("modifiers", modifiers(mods))
+                .with("pattern", pattern(pat))
+                .with("typ", optional(typ, type_expr))
+                .with("value", expr(value))
+                .done()
+        }
+        StmtKind::Function { modifiers: mods, name: func, type_params, params, ret, where_clause, body } => {
+            node("Function")
+                .with("modifiers", modifiers(mods))
+                .with("name", name(func))
+                .with("type_params", list(type_params, type_param))
+                .with("params", list(params, expr))
+                .with("ret", optional(ret, type_expr))
+                .with("where_clause", list(where_clause, constraint))
+                .with("body", block(body))
+                .done()
+        }
+        StmtKind::Class(decl) => node("Class").with("decl", class(decl)).done(),
+        StmtKind::Enum(decl) => node("Enum").with("decl", enumeration(decl)).done(),
+        StmtKind::Import(decl) => node("Import").with("decl", import(decl)).done(),
+        StmtKind::Return { value } => node("Return").with("value", optional(value, expr)).done(),
+        StmtKind::If { cond, then, otherwise } => {
+            node("If")
+                .with("cond", expr(cond))
+                .with("then", block(then))
+                .with("otherwise", optional(otherwise, |otherwise| block(otherwise)))
+                .done()
+        }
+        StmtKind::Match { value, arms } => {
+            node("Match").with("value", expr(value)).with("arms", list(arms, match_arm)).done()
+        }
+        StmtKind::For { binding, iterable, body } => {
+            node("For")
+                .with("binding", pattern(binding))
+                .with("iterable", expr(iterable))
+                .with("body", block(body))
+                .done()
+        }
+        StmtKind::Expression { expr: inner } => node("Expression").with("expr", expr(inner)).done(),
+        StmtKind::FieldInit { name: field, value } => {
+            node("FieldInit").with("name", name(field)).with("value", expr(value)).done()
+        }
+        StmtKind::Error => node("Error").done(),
+    };
+}
+
+fn class(decl: &ClassDecl) -> Value {
+    return Node::bare("ClassDecl")
+        .with("modifiers", modifiers(&decl.modifiers))
+        .with("name", name(&decl.name))
+        .with("type_params", list(&decl.type_params, type_param))
+        .with("where_clause", list(&decl.where_clause, constraint))
+        .with("fields", list(&decl.fields, field))
+        .with("methods", block(&decl.methods))
+        .with("constructor", optional(&decl.constructor, |constructor| stmt(constructor)))
+        .done();
+}
+
+fn field(field: &FieldDecl) -> Value {
+    return Node::new("FieldDecl", &field.span, field.uid)
+        .with("name", name(&field.name))
+        .with("ty", type_expr(&field.ty))
+        .done();
+}
+
+fn type_param(param: &TypeParam) -> Value {
+    return Node::new("TypeParam", &param.span, param.uid).with("name", name(&param.name)).done();
+}
+
+fn constraint(constraint: &Constraint) -> Value {
+    return Node::new("Constraint", &constraint.span, constraint.uid)
+        .with("param", name(&constraint.param))
+        .with("bound", name(&constraint.bound))
+        .done();
+}
+
+fn enumeration(decl: &EnumDecl) -> Value {
+    return Node::bare("EnumDecl")
+        .with("modifiers", modifiers(&decl.modifiers))
+        .with("name", name(&decl.name))
+        .with("variants", list(&decl.variants, variant))
+        .done();
+}
+
+fn variant(variant: &VariantDecl) -> Value {
+    return Node::new("VariantDecl", &variant.span, variant.uid)
+        .with("name", name(&variant.name))
+        .with("payload", list(&variant.payload, type_expr))
+        .done();
+}
+
+fn import(decl: &ImportDecl) -> Value {
+    let imported = match &decl.imported {
+        Imported::Module => Value::Name("module".to_string()),
+        Imported::Everything => Value::Name("*".to_string()),
+        Imported::Items(items) => list(items, import_item),
+    };
+    return Node::bare("ImportDecl")
+        .with("path", list(&decl.path, name))
+        .with("alias", optional(&decl.alias, name))
+        .with("imported", imported)
+        .done();
+}
+
+fn import_item(item: &ImportItem) -> Value {
+    return Node::new("ImportItem", &item.span, item.uid)
+        .with("name", name(&item.name))
+        .with("alias", optional(&item.alias, name))
+        .done();
+}
+
+fn match_arm(arm: &MatchArm) -> Value {
+    return Node::new("MatchArm", &arm.span, arm.uid)
+        .with("pattern", pattern(&arm.pattern))
+        .with("guard", optional(&arm.guard, expr))
+        .with("body", block(&arm.body))
+        .done();
+}
+
+// ----------------------------------------------------------------- \\
+// EXPRESSIONS
+// ----------------------------------------------------------------- \\
+
+pub fn expr(expr_: &Expr) -> Value {
+    let node = |kind| Node::new(kind, &expr_.span, expr_.uid);
+    return match &expr_.kind {
+        ExprKind::Integer { value } => node("Integer").with("value", Value::Int(*value as i64)).done(),
+        ExprKind::Float { value } => node("Float").with("value", Value::Float(*value)).done(),
+        ExprKind::String { value } => node("String").with("value", Value::Str(value.to_owned())).done(),
+        ExprKind::Boolean { value } => node("Boolean").with("value", Value::Bool(*value)).done(),
+        ExprKind::Ident { name: ident } => node("Ident").with("name", name(ident)).done(),
+        ExprKind::ArrayLiteral { elements } => node("ArrayLiteral").with("elements", list(elements, expr)).done(),
+        ExprKind::ArrayRepeat { value, count } => {
+            node("ArrayRepeat").with("value", expr(value)).with("count", expr(count)).done()
+        }
+        ExprKind::SeqLiteral { elements } => node("SeqLiteral").with("elements", list(elements, expr)).done(),
+        ExprKind::Tuple { elements } => node("Tuple").with("elements", list(elements, expr)).done(),
+        ExprKind::Call { callee, args } => {
+            node("Call").with("callee", expr(callee)).with("args", list(args, expr)).done()
+        }
+        ExprKind::Field { object, name: field } => {
+            node("Field").with("object", expr(object)).with("name", name(field)).done()
+        }
+        ExprKind::Index { object, index } => {
+            node("Index").with("object", expr(object)).with("index", expr(index)).done()
+        }
+        ExprKind::Range { start, end, inclusive } => {
+            node("Range")
+                .with("start", optional(start, |start| expr(start)))
+                .with("end", optional(end, |end| expr(end)))
+                .with("inclusive", Value::Bool(*inclusive))
+                .done()
+        }
+        ExprKind::Variant { enum_name, variant, args } => {
+            node("Variant")
+                .with("enum_name", name(enum_name))
+                .with("variant", name(variant))
+                .with("args", list(args, expr))
+                .done()
+        }
+        ExprKind::TypeApply { callee, args } => {
+            node("TypeApply").with("callee", expr(callee)).with("args", list(args, type_expr)).done()
+        }
+        ExprKind::Assignment { assignee, value, op } => {
+            node("Assignment")
+                .with("op", Value::Name(op.to_string()))
+                .with("assignee", expr(assignee))
+                .with("value", expr(value))
+                .done()
+        }
+        ExprKind::Parameter { name: param, ty } => {
+            node("Parameter")
+                .with("name", name(param))
+                .with("ty", optional(ty, |ty| type_expr(ty)))
+                .done()
+        }
+        ExprKind::Lambda { params, ret, body } => {
+            node("Lambda")
+                .with("params", list(params, expr))
+                .with("ret", optional(ret, type_expr))
+                .with("body", block(body))
+                .done()
+        }
+        ExprKind::Binary { lhs, rhs, op } => {
+            node("Binary")
+                .with("op", Value::Name(op.to_string()))
+                .with("lhs", expr(lhs))
+                .with("rhs", expr(rhs))
+                .done()
+        }
+        ExprKind::Is { value, ty, negated } => {
+            node("Is")
+                .with("value", expr(value))
+                .with("ty", type_expr(ty))
+                .with("negated", Value::Bool(*negated))
+                .done()
+        }
+        ExprKind::Default => node("Default").done(),
+        ExprKind::Error => node("Error").done(),
+    };
+}
+
+pub fn type_expr(ty: &TypeExpr) -> Value {
+    let node = |kind| Node::new(kind, &ty.span, ty.uid);
+    return match &ty.kind {
+        TypeKind::Named { name: named } => node("Named").with("name", name(named)).done(),
+        TypeKind::Generic { name: generic, args } => {
+            node("Generic").with("name", name(generic)).with("args", list(args, type_expr)).done()
+        }
+        TypeKind::Nullable { inner } => node("Nullable").with("inner", type_expr(inner)).done(),
+        TypeKind::Array { elem, size } => {
+            node("Array").with("elem", type_expr(elem)).with("size", expr(size)).done()
+        }
+        TypeKind::Sequence { elem } => node("Sequence").with("elem", type_expr(elem)).done(),
+        TypeKind::Tuple { elems } => node("Tuple").with("elems", list(elems, type_expr)).done(),
+        TypeKind::Function { params, ret } => {
+            node("Function")
+                .with("params", list(params, type_expr))
+                .with("ret", optional(ret, |ret| type_expr(ret)))
+                .done()
+        }
+        TypeKind::Error => node("Error").done(),
+    };
+}
+
+pub fn pattern(pattern_: &Pattern) -> Value {
+    let node = |kind| Node::new(kind, &pattern_.span, pattern_.uid);
+    return match &pattern_.kind {
+        PatternKind::Wildcard => node("Wildcard").done(),
+        PatternKind::None => node("None").done(),
+        PatternKind::Binding { name: bound } => node("Binding").with("name", name(bound)).done(),
+        PatternKind::Literal { value } => node("Literal").with("value", expr(value)).done(),
+        PatternKind::Tuple { elements } => node("Tuple").with("elements", list(elements, pattern)).done(),
+        PatternKind::Variant { enum_name, variant, fields } => {
+            node("Variant")
+                .with("enum_name", name(enum_name))
+                .with("variant", name(variant))
+                .with("fields", list(fields, pattern))
+                .done()
+        }
+        PatternKind::Error => node("Error").done(),
+    };
+}
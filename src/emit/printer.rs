@@ -0,0 +1,600 @@
+use crate::parser::ast::{
+    ClassDecl,
+    Constraint,
+    EnumDecl,
+    Expr,
+    ExprKind,
+    ImportDecl,
+    Imported,
+    MatchArm,
+    Modifier,
+    Operator,
+    Pattern,
+    PatternKind,
+    Stmt,
+    StmtKind,
+    TypeExpr,
+    TypeKind,
+    TypeParam,
+};
+
+/// Turns a program back into kaska source with canonical indentation and spacing.
+/// Parsing the output gives back a tree of the same shape, though pipes come out
+/// desugared and redundant parentheses are dropped.
+pub fn print(stmts: &[Stmt]) -> String {
+    let mut printer = Printer::new();
+    printer.program(stmts);
+    return printer.out;
+}
+
+/// Prints a single expression, mostly useful for diagnostics
+pub fn print_expr(expr: &Expr) -> String {
+    let mut printer = Printer::new();
+    printer.expr(expr);
+    return printer.out;
+}
+
+/// Prints a single type expression
+pub fn print_type(ty: &TypeExpr) -> String {
+    let mut printer = Printer::new();
+    printer.ty(ty);
+    return printer.out;
+}
+
+const INDENT: &str = "    ";
+
+/// How tightly an expression holds together. Anything that binds looser than
+/// the place it is printed in allows gets wrapped in parentheses.
+fn strength(expr: &Expr) -> u8 {
+    return match &expr.kind {
+        ExprKind::Assignment { .. } => Operator::Eq.precedence(),
+        ExprKind::Range { .. } => Operator::Pipe.precedence(),
+        ExprKind::Binary { op, .. } => op.precedence(),
+        ExprKind::Is { .. } => Operator::Lt.precedence(),
+        _ => u8::MAX,
+    };
+}
+
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+// ----------------------------------------------------------------- \\
+// HELPER METHODS
+// ----------------------------------------------------------------- \\
+
+impl Printer {
+    fn new() -> Printer {
+        return Printer { out: String::new(), indent: 0 };
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Ends the current line and indents the next one
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// Writes `items` separated by commas
+    fn list<T>(&mut self, items: &[T], mut item: impl FnMut(&mut Printer, &T)) {
+        for (i, element) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            item(self, element);
+        }
+    }
+
+    /// Writes a tuple of `items`, where a single item needs a trailing comma to
+    /// not be read back as a parenthesized value
+    fn tuple<T>(&mut self, items: &[T], item: impl FnMut(&mut Printer, &T)) {
+        self.write("(");
+        self.list(items, item);
+        if items.len() == 1 {
+            self.write(",");
+        }
+        self.write(")");
+    }
+
+    /// Writes the statements of a block one level deeper, followed by END on its own line
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.indent += 1;
+        for stmt in stmts {
+            self.newline();
+            self.stmt(stmt);
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("end");
+    }
+
+    fn modifiers(&mut self, modifiers: &[Modifier]) {
+        for modifier in modifiers {
+            self.write(&format!("{modifier} "));
+        }
+    }
+
+    fn type_params(&mut self, params: &[TypeParam]) {
+        if params.is_empty() {
+            return;
+        }
+        self.write("{");
+        self.list(params, |p, param| p.write(&param.name));
+        self.write("}");
+    }
+
+    /// Writes ` where T: A + B, U: C`, folding consecutive bounds on the same parameter
+    fn where_clause(&mut self, constraints: &[Constraint]) {
+        if constraints.is_empty() {
+            return;
+        }
+        self.write(" where ");
+
+        let mut previous: Option<&str> = None;
+        for constraint in constraints {
+            if previous == Some(constraint.param.as_str()) {
+                self.write(&format!(" + {}", constraint.bound));
+            } else {
+                if previous.is_some() {
+                    self.write(", ");
+                }
+                self.write(&format!("{}: {}", constraint.param, constraint.bound));
+            }
+            previous = Some(&constraint.param);
+        }
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// STATEMENTS
+// ----------------------------------------------------------------- \\
+
+impl Printer {
+    /// Top-level statements go one per line, with block declarations set apart by blank lines
+    fn program(&mut self, stmts: &[Stmt]) {
+        let is_decl = |stmt: &Stmt| {
+            matches!(stmt.kind, StmtKind::Function { .. } | StmtKind::Class(_) | StmtKind::Enum(_))
+        };
+
+        for (i, stmt) in stmts.iter().enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+                if is_decl(stmt) || is_decl(&stmts[i - 1]) {
+                    self.out.push('\n');
+                }
+            }
+            self.stmt(stmt);
+        }
+        if !stmts.is_empty() {
+            self.out.push('\n');
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Variable { modifiers, pattern, typ, value } => {
+                self.modifiers(modifiers);
+                self.write("let ");
+                self.pattern(pattern);
+                if let Some(typ) = typ {
+                    self.write(": ");
+                    self.ty(typ);
+                }
+                self.write(" = ");
+                self.expr(value);
+            }
+            StmtKind::Function { modifiers, name, type_params, params, ret, where_clause, body } => {
+                self.modifiers(modifiers);
+                self.write(&format!("function {name}"));
+                self.type_params(type_params);
+                self.write("(");
+                self.list(params, Printer::expr);
+                self.write(")");
+                if let Some(ret) = ret {
+                    self.write(" -> ");
+                    self.ty(ret);
+                }
+                self.where_clause(where_clause);
+                self.block(body);
+            }
+            StmtKind::Class(decl) => self.class(decl),
+            StmtKind::Enum(decl) => self.enumeration(decl),
+            StmtKind::Import(decl) => self.import(decl),
+            StmtKind::Return { value } => {
+                self.write("return");
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.expr(value);
+                }
+            }
+            StmtKind::If { cond, then, otherwise } => {
+                self.write("if ");
+                self.expr(cond);
+                self.write(" then");
+                self.if_rest(then, otherwise);
+            }
+            StmtKind::Match { value, arms } => {
+                self.write("match ");
+                self.expr(value);
+                self.indent += 1;
+                for arm in arms {
+                    self.newline();
+                    self.match_arm(arm);
+                }
+                self.indent -= 1;
+                self.newline();
+                self.write("end");
+            }
+            StmtKind::For { binding, iterable, body } => {
+                self.write("for ");
+                self.pattern(binding);
+                self.write(" in ");
+                self.expr(iterable);
+                self.write(" do");
+                self.block(body);
+            }
+            StmtKind::Expression { expr } => self.expr(expr),
+            StmtKind::FieldInit { name, value } => {
+                self.write(&format!("let self.{name} = "));
+                self.expr(value);
+            }
+            StmtKind::Error => self.write("<error>"),
+        }
+    }
+
+    /// Writes the branches of an if statement after its THEN, where an else branch
+    /// made up of a single if statement is an `else if`
+    fn if_rest(&mut self, then: &[Stmt], otherwise: &Option<Vec<Stmt>>) {
+        self.indent += 1;
+        for stmt in then {
+            self.newline();
+            self.stmt(stmt);
+        }
+        self.indent -= 1;
+
+        match otherwise.as_deref() {
+            Some([Stmt { kind: StmtKind::If { cond, then, otherwise }, .. }]) => {
+                self.newline();
+                self.write("else if ");
+                self.expr(cond);
+                self.write(" then");
+                self.if_rest(then, otherwise);
+            }
+            Some(otherwise) => {
+                self.newline();
+                self.write("else");
+                self.block(otherwise);
+            }
+            None => {
+                self.newline();
+                self.write("end");
+            }
+        }
+    }
+
+    fn match_arm(&mut self, arm: &MatchArm) {
+        self.write("case ");
+        self.pattern(&arm.pattern);
+        if let Some(guard) = &arm.guard {
+            self.write(" if ");
+            self.expr(guard);
+        }
+        self.write(" then");
+
+        self.indent += 1;
+        for stmt in &arm.body {
+            self.newline();
+            self.stmt(stmt);
+        }
+        self.indent -= 1;
+    }
+
+    fn class(&mut self, decl: &ClassDecl) {
+        self.modifiers(&decl.modifiers);
+        self.write(&format!("class {}", decl.name));
+        self.type_params(&decl.type_params);
+        self.where_clause(&decl.where_clause);
+
+        self.indent += 1;
+        for field in &decl.fields {
+            self.newline();
+            self.write(&format!("{}: ", field.name));
+            self.ty(&field.ty);
+        }
+
+        let methods = decl.constructor.iter().map(|constructor| &**constructor).chain(&decl.methods);
+        for (i, method) in methods.enumerate() {
+            if i > 0 || !decl.fields.is_empty() {
+                self.out.push('\n');
+            }
+            self.newline();
+            self.stmt(method);
+        }
+        self.indent -= 1;
+
+        self.newline();
+        self.write("end");
+    }
+
+    fn enumeration(&mut self, decl: &EnumDecl) {
+        self.modifiers(&decl.modifiers);
+        self.write(&format!("enum {}", decl.name));
+
+        self.indent += 1;
+        for variant in &decl.variants {
+            self.newline();
+            self.write(&variant.name);
+            if !variant.payload.is_empty() {
+                self.write("(");
+                self.list(&variant.payload, Printer::ty);
+                self.write(")");
+            }
+        }
+        self.indent -= 1;
+
+        self.newline();
+        self.write("end");
+    }
+
+    fn import(&mut self, decl: &ImportDecl) {
+        let path = decl.path.join(".");
+        match &decl.imported {
+            Imported::Module => {
+                self.write(&format!("import {path}"));
+                if let Some(alias) = &decl.alias {
+                    self.write(&format!(" as {alias}"));
+                }
+            }
+            Imported::Everything => self.write(&format!("from {path} import * end")),
+            Imported::Items(items) => {
+                self.write(&format!("from {path} import "));
+                self.list(items, |p, item| {
+                    p.write(&item.name);
+                    if let Some(alias) = &item.alias {
+                        p.write(&format!(" as {alias}"));
+                    }
+                });
+                self.write(" end");
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// EXPRESSIONS
+// ----------------------------------------------------------------- \\
+
+impl Printer {
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Integer { value } => self.write(&value.to_string()),
+            ExprKind::Float { value } => self.float(*value),
+            ExprKind::String { value } => self.string(value),
+            ExprKind::Boolean { value } => self.write(&value.to_string()),
+            ExprKind::Ident { name } => self.write(name),
+
+            ExprKind::ArrayLiteral { elements } => {
+                self.write("[");
+                self.list(elements, Printer::expr);
+                self.write("]");
+            }
+            ExprKind::ArrayRepeat { value, count } => {
+                self.write("[");
+                self.expr(value);
+                self.write("; ");
+                self.expr(count);
+                self.write("]");
+            }
+            ExprKind::SeqLiteral { elements } => {
+                self.write("{");
+                self.list(elements, Printer::expr);
+                self.write("}");
+            }
+            ExprKind::Tuple { elements } => self.tuple(elements, Printer::expr),
+
+            ExprKind::Call { callee, args } => {
+                self.operand(callee, u8::MAX);
+                self.write("(");
+                self.list(args, Printer::expr);
+                self.write(")");
+            }
+            ExprKind::Field { object, name } => {
+                self.operand(object, u8::MAX);
+                self.write(&format!(".{name}"));
+            }
+            ExprKind::Index { object, index } => {
+                self.operand(object, u8::MAX);
+                self.write("[");
+                self.operand(index, Operator::Pipe.precedence());
+                self.write("]");
+            }
+            ExprKind::Range { start, end, inclusive } => {
+                let bound = Operator::Pipe.precedence() + 1;
+                if let Some(start) = start {
+                    self.operand(start, bound);
+                }
+                self.write(if *inclusive { "..=" } else { ".." });
+                if let Some(end) = end {
+                    self.operand(end, bound);
+                }
+            }
+            ExprKind::Variant { enum_name, variant, args } => {
+                self.write(&format!("{enum_name}.{variant}"));
+                if !args.is_empty() {
+                    self.write("(");
+                    self.list(args, Printer::expr);
+                    self.write(")");
+                }
+            }
+            ExprKind::TypeApply { callee, args } => {
+                self.operand(callee, u8::MAX);
+                self.write("{");
+                self.list(args, Printer::ty);
+                self.write("}");
+            }
+            ExprKind::Assignment { assignee, value, op } => {
+                self.operand(assignee, Operator::Pipe.precedence());
+                self.write(&format!(" {op} "));
+                self.expr(value);
+            }
+            ExprKind::Parameter { name, ty } => {
+                self.write(name);
+                if let Some(ty) = ty {
+                    self.write(": ");
+                    self.ty(ty);
+                }
+            }
+            ExprKind::Lambda { params, ret, body } => {
+                self.write("function(");
+                self.list(params, Printer::expr);
+                self.write(")");
+                if let Some(ret) = ret {
+                    self.write(" -> ");
+                    self.ty(ret);
+                }
+                self.block(body);
+            }
+
+            ExprKind::Binary { lhs, rhs, op } => {
+                let prec = op.precedence();
+                let (mut left, right) = if op.right_assoc() { (prec + 1, prec) } else { (prec, prec + 1) };
+
+                // `x is T < y` would read `T<y>` as a generic type
+                if *op == Operator::Lt && matches!(lhs.kind, ExprKind::Is { .. }) {
+                    left = u8::MAX;
+                }
+                self.operand(lhs, left);
+                self.write(&format!(" {op} "));
+                self.operand(rhs, right);
+            }
+            ExprKind::Is { value, ty, negated } => {
+                self.operand(value, Operator::Lt.precedence());
+                self.write(if *negated { " is not " } else { " is " });
+                self.ty(ty);
+            }
+
+            ExprKind::Default => self.write("default"),
+            ExprKind::Error => self.write("<error>"),
+        }
+    }
+
+    /// Writes an expression that has to bind at least as tightly as `min`,
+    /// wrapping it in parentheses otherwise
+    fn operand(&mut self, expr: &Expr, min: u8) {
+        if strength(expr) >= min {
+            self.expr(expr);
+            return;
+        }
+        self.write("(");
+        self.expr(expr);
+        self.write(")");
+    }
+
+    /// Floats are written out in full with a fractional part, the only form the
+    /// lexer reads back, so `1e20` prints as `100000000000000000000.0`
+    fn float(&mut self, value: f32) {
+        let mut text = value.to_string();
+        if !text.contains('.') {
+            text.push_str(".0");
+        }
+        self.write(&text);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.out.push('"');
+        for ch in value.chars() {
+            match ch {
+                '\n' => self.write("\\n"),
+                '\t' => self.write("\\t"),
+                '\r' => self.write("\\r"),
+                '\0' => self.write("\\0"),
+                '\\' => self.write("\\\\"),
+                '"' => self.write("\\\""),
+                '$' => self.write("\\$"),
+                _ => self.out.push(ch),
+            }
+        }
+        self.out.push('"');
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// TYPES AND PATTERNS
+// ----------------------------------------------------------------- \\
+
+impl Printer {
+    fn ty(&mut self, ty: &TypeExpr) {
+        match &ty.kind {
+            TypeKind::Named { name } => self.write(name),
+            TypeKind::Generic { name, args } => {
+                self.write(name);
+                self.write("{");
+                self.list(args, Printer::ty);
+                self.write("}");
+            }
+            TypeKind::Nullable { inner } => {
+                self.postfix_type(inner);
+                self.write("?");
+            }
+            TypeKind::Array { elem, size } => {
+                self.write("[");
+                self.ty(elem);
+                self.write("; ");
+                self.expr(size);
+                self.write("]");
+            }
+            TypeKind::Sequence { elem } => {
+                self.postfix_type(elem);
+                self.write("{}");
+            }
+            TypeKind::Tuple { elems } => self.tuple(elems, Printer::ty),
+            TypeKind::Function { params, ret } => {
+                self.write("function(");
+                self.list(params, Printer::ty);
+                self.write(")");
+                if let Some(ret) = ret {
+                    self.write(" -> ");
+                    self.ty(ret);
+                }
+            }
+            TypeKind::Error => self.write("<error>"),
+        }
+    }
+
+    /// Writes the type in front of a `?` or `{}`, which would otherwise end up
+    /// applying to the return type of a function type
+    fn postfix_type(&mut self, ty: &TypeExpr) {
+        if let TypeKind::Function { .. } = ty.kind {
+            self.write("(");
+            self.ty(ty);
+            self.write(")");
+        } else {
+            self.ty(ty);
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Wildcard => self.write("_"),
+            PatternKind::None => self.write("None"),
+            PatternKind::Binding { name } => self.write(name),
+            PatternKind::Literal { value } => self.expr(value),
+            PatternKind::Tuple { elements } => self.tuple(elements, Printer::pattern),
+            PatternKind::Variant { enum_name, variant, fields } => {
+                self.write(&format!("{enum_name}.{variant}"));
+                if !fields.is_empty() {
+                    self.write("(");
+                    self.list(fields, Printer::pattern);
+                    self.write(")");
+                }
+            }
+            PatternKind::Error => self.write("<error>"),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{ emit::node::{ self, Value }, parser::ast::Stmt };
+
+/// The tree as S-expressions, one statement per line. Nodes become `(Kind fields...)`
+/// with their fields in AST order, lists are written `[a b]` and missing values `nil`.
+/// Spans and uids are left out so the output only changes when the tree does.
+pub fn ast(stmts: &[Stmt]) -> String {
+    let mut out = String::new();
+    for stmt in stmts {
+        write(&mut out, &node::stmt(stmt));
+        out.push('\n');
+    }
+    return out;
+}
+
+pub fn sexp(value: &Value) -> String {
+    let mut out = String::new();
+    write(&mut out, value);
+    return out;
+}
+
+fn write(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("nil"),
+        Value::Bool(value) => out.push_str(&value.to_string()),
+        Value::Int(value) => out.push_str(&value.to_string()),
+        Value::Float(value) => out.push_str(&format!("{value:?}")),
+        Value::Str(value) => out.push_str(&format!("{value:?}")),
+        Value::Name(value) => out.push_str(value),
+        Value::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                write(out, item);
+            }
+            out.push(']');
+        }
+        Value::Node(node) => {
+            out.push('(');
+            out.push_str(&node.kind);
+            for (_, value) in &node.fields {
+                out.push(' ');
+                write(out, value);
+            }
+            out.push(')');
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use crate::{
+    common::{ errors::ErrorBuffer, span::Span },
+    lexer::{ lexer::Lexer, token::{ Tk, Token } },
+    parse,
+    parser::ast::{ Operator, TypeExpr, Visitor },
+};
+
+/// Formats kaska source. Only the layout changes: blocks are indented by four
+/// spaces, operators and punctuation get canonical spacing and declarations are
+/// set apart by a blank line. Comments are kept where they are, and code that
+/// doesn't lex or parse is given back as its errors instead.
+pub fn format(source: &str) -> Result<String, ErrorBuffer> {
+    let (tokens, errors) = Lexer::with_trivia(source).lex();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let code = tokens
+        .iter()
+        .filter(|tk| !matches!(tk.kind, Tk::Comment | Tk::Whitespace))
+        .map(Token::copy)
+        .collect();
+    let (ast, errors) = parse(code);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut types = TypeSpans(vec![]);
+    types.visit_block(&ast);
+
+    let mut formatter = Formatter { source, types: types.0, stack: vec![], lines: vec![] };
+    for line in tokens.split(|tk| tk.kind == Tk::Newline) {
+        let line: Vec<&Token> = line
+            .iter()
+            .filter(|tk| !matches!(tk.kind, Tk::Whitespace | Tk::EOF))
+            .collect();
+        formatter.line(&line);
+    }
+    return Ok(formatter.finish());
+}
+
+const INDENT: &str = "    ";
+
+/// Collects where every type expression is, which tells function types apart from
+/// functions and generic brackets apart from comparisons
+struct TypeSpans(Vec<Span>);
+
+impl Visitor for TypeSpans {
+    fn visit_type(&mut self, ty: &TypeExpr) {
+        self.0.push(ty.span.clone());
+        self.walk_type(ty);
+    }
+}
+
+/// Something opened on an earlier line that the lines after it are indented under
+struct Open {
+    kind: Tk,
+    indent: usize,
+    line: usize,
+}
+
+struct Line {
+    indent: usize,
+    text: String,
+    blank: bool,
+    comment: bool,
+
+    /// Ends with something left open, so the line after it starts a block
+    opens: bool,
+    /// Starts with END, ELSE, CASE or a closing bracket
+    closes: bool,
+    /// Ends in an operator, so the expression carries on onto the next line
+    continues: bool,
+    decl_start: bool,
+    decl_end: bool,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    types: Vec<Span>,
+    stack: Vec<Open>,
+    lines: Vec<Line>,
+}
+
+// ----------------------------------------------------------------- \\
+// INDENTATION
+// ----------------------------------------------------------------- \\
+
+impl<'a> Formatter<'a> {
+    /// How far the body of whatever is open right now is indented
+    fn depth(&self) -> usize {
+        return self.stack.last().map_or(0, |open| open.indent + 1);
+    }
+
+    fn in_type(&self, tk: &Token) -> bool {
+        return self.types.iter().any(|span| span.contains(&tk.span.start));
+    }
+
+    /// Whether `tk` opens a block that is closed by END. `prev` is the token before
+    /// it on the same line and `case_header` whether it sits between CASE and THEN.
+    fn opens_block(&self, tk: &Token, prev: Option<&Token>, case_header: bool) -> bool {
+        return match tk.kind {
+            Tk::Class | Tk::Enum | Tk::For | Tk::Match | Tk::From => true,
+            Tk::If => !case_header && prev.is_none_or(|prev| prev.kind != Tk::Else),
+            Tk::Function => !self.in_type(tk),
+            _ => false,
+        };
+    }
+
+    /// Pops whatever `tk` closes and gives back what it was
+    fn close(&mut self, tk: &Token) -> Option<Open> {
+        let opener = match tk.kind {
+            Tk::RParen => Tk::LParen,
+            Tk::RBrac => Tk::LBrac,
+            Tk::RCurl => Tk::LCurl,
+            _ => {
+                // END closes the last arm of a match along with the match itself
+                if self.stack.last().is_some_and(|open| open.kind == Tk::Case) {
+                    self.stack.pop();
+                }
+                return self.stack.pop();
+            }
+        };
+        if self.stack.last().is_some_and(|open| open.kind == opener) {
+            return self.stack.pop();
+        }
+        return None;
+    }
+
+    /// Whether closing `open` finishes a top-level declaration, given the index of
+    /// the line being laid out and whether that line starts one
+    fn ends_declaration(&self, open: &Open, index: usize, decl_start: bool) -> bool {
+        let started = if open.line == index { decl_start } else { self.lines[open.line].decl_start };
+        return self.stack.is_empty() && started;
+    }
+
+    fn line(&mut self, tokens: &[&Token]) {
+        let index = self.lines.len();
+        let Some(first) = tokens.first() else {
+            self.lines.push(Line {
+                indent: 0,
+                text: String::new(),
+                blank: true,
+                comment: false,
+                opens: false,
+                closes: false,
+                continues: false,
+                decl_start: false,
+                decl_end: false,
+            });
+            return;
+        };
+
+        let decl_start =
+            self.stack.is_empty() &&
+            matches!(first.kind, Tk::Function | Tk::Class | Tk::Enum | Tk::Pub | Tk::Inline | Tk::Static) &&
+            !self.in_type(first);
+        let mut indent = self.depth();
+        let mut decl_end = false;
+        let mut rest = tokens;
+
+        // closers at the start of the line pull it back to where they were opened
+        let closes = matches!(first.kind, Tk::End | Tk::Else | Tk::Case | Tk::RParen | Tk::RBrac | Tk::RCurl);
+        match first.kind {
+            Tk::Else => {
+                indent = self.stack.last().map_or(0, |open| open.indent);
+            }
+            Tk::Case => {}
+            _ => {
+                let mut leading = 0;
+                while let Some(tk) = rest.first() {
+                    if !matches!(tk.kind, Tk::End | Tk::RParen | Tk::RBrac | Tk::RCurl) {
+                        break;
+                    }
+                    let Some(open) = self.close(tk) else {
+                        break;
+                    };
+                    indent = open.indent;
+                    decl_end = self.ends_declaration(&open, index, decl_start);
+                    leading += 1;
+                    rest = &rest[1..];
+                }
+
+                // a line continuing an expression goes one deeper
+                let continues = self.lines
+                    .iter()
+                    .rev()
+                    .find(|line| !line.blank && !line.comment)
+                    .is_some_and(|line| line.continues);
+                if leading == 0 && (first.kind == Tk::PipeMore || continues) {
+                    indent += 1;
+                }
+            }
+        }
+
+        let mut case_header = false;
+        let mut prev: Option<&Token> = None;
+        for &tk in rest {
+            match tk.kind {
+                Tk::Case => {
+                    if self.stack.last().is_some_and(|open| open.kind == Tk::Case) {
+                        self.stack.pop();
+                    }
+                    if prev.is_none() {
+                        indent = self.depth();
+                    }
+                    self.stack.push(Open { kind: Tk::Case, indent, line: index });
+                    case_header = true;
+                }
+                Tk::Then => case_header = false,
+                Tk::LParen | Tk::LBrac | Tk::LCurl => {
+                    self.stack.push(Open { kind: tk.kind, indent, line: index });
+                }
+                Tk::End | Tk::RParen | Tk::RBrac | Tk::RCurl => {
+                    if let Some(open) = self.close(tk) {
+                        decl_end |= self.ends_declaration(&open, index, decl_start);
+                    }
+                }
+                _ if self.opens_block(tk, prev, case_header) => {
+                    self.stack.push(Open { kind: tk.kind, indent, line: index });
+                }
+                _ => {}
+            }
+            prev = Some(tk);
+        }
+
+        let opens = self.stack.last().is_some_and(|open| open.line == index);
+        let continues = tokens
+            .iter()
+            .rev()
+            .find(|tk| tk.kind != Tk::Comment)
+            .is_some_and(|tk| continues_after(&tk.kind) && !self.in_type(tk));
+
+        let text = self.spaced(tokens);
+        self.lines.push(Line {
+            indent,
+            text,
+            blank: false,
+            comment: first.kind == Tk::Comment,
+            opens,
+            closes,
+            continues,
+            decl_start,
+            decl_end,
+        });
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// SPACING
+// ----------------------------------------------------------------- \\
+
+impl<'a> Formatter<'a> {
+    fn text(&self, tk: &Token) -> String {
+        let text = self.source.get(tk.span.clone()).unwrap_or(&tk.lexeme);
+        return text.trim_end().to_string();
+    }
+
+    /// Joins the tokens of a line, with spaces around operators and keywords but
+    /// not inside of brackets or around `.`, `..` and the like
+    fn spaced(&self, tokens: &[&Token]) -> String {
+        let mut text = String::new();
+
+        // generic brackets `<T>` are glued to what they apply to, unlike comparisons
+        let mut angles = 0usize;
+        let mut prev: Option<(&Token, bool)> = None;
+
+        for (i, &tk) in tokens.iter().enumerate() {
+            let generic = match tk.kind {
+                Tk::Less => {
+                    let declares = i >= 2 &&
+                        tokens[i - 1].kind == Tk::Ident &&
+                        matches!(tokens[i - 2].kind, Tk::Function | Tk::Class);
+                    declares || self.in_type(tk)
+                }
+                Tk::More => angles > 0,
+                _ => false,
+            };
+            match (tk.kind, generic) {
+                (Tk::Less, true) => angles += 1,
+                (Tk::More, true) => angles -= 1,
+                _ => {}
+            }
+
+            if let Some((prev, prev_generic)) = prev && Self::space_between(prev, prev_generic, tk, generic) {
+                text.push(' ');
+            }
+            text.push_str(&self.text(tk));
+            prev = Some((tk, generic));
+        }
+        return text;
+    }
+
+    fn space_between(prev: &Token, prev_generic: bool, next: &Token, next_generic: bool) -> bool {
+        if next.kind == Tk::Comment {
+            return true;
+        }
+
+        // nothing after an opening bracket or before a closing one
+        if matches!(prev.kind, Tk::LParen | Tk::LBrac | Tk::LCurl | Tk::Dot | Tk::DotDot | Tk::DotDotEqual | Tk::Sigil) {
+            return false;
+        }
+        if prev.kind == Tk::Less && prev_generic {
+            return false;
+        }
+        if
+            matches!(
+                next.kind,
+                Tk::RParen |
+                    Tk::RBrac |
+                    Tk::RCurl |
+                    Tk::Comma |
+                    Tk::Semicolon |
+                    Tk::Colon |
+                    Tk::Dot |
+                    Tk::DotDot |
+                    Tk::DotDotEqual |
+                    Tk::Question
+            ) ||
+            next_generic
+        {
+            return false;
+        }
+
+        // calls, indexing and type arguments hug what they apply to
+        let applies = matches!(
+            prev.kind,
+            Tk::Ident | Tk::String | Tk::Function | Tk::RParen | Tk::RBrac | Tk::RCurl | Tk::Question
+        ) || (prev.kind == Tk::More && prev_generic);
+        if matches!(next.kind, Tk::LParen | Tk::LBrac | Tk::LCurl) && applies {
+            return false;
+        }
+        return true;
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// BLANK LINES
+// ----------------------------------------------------------------- \\
+
+impl<'a> Formatter<'a> {
+    /// Lays the lines out, keeping at most one blank line in a row and none at the
+    /// edges of a block. Top-level declarations always get one around them, which
+    /// goes above any comments that lead into the declaration.
+    fn finish(self) -> String {
+        let lines = self.lines;
+        let mut forced = vec![false; lines.len()];
+
+        for (i, line) in lines.iter().enumerate() {
+            if line.decl_start {
+                let mut start = i;
+                while start > 0 && lines[start - 1].comment && lines[start - 1].indent == 0 {
+                    start -= 1;
+                }
+                forced[start] = true;
+            }
+            if line.decl_end && let Some(next) = (i + 1..lines.len()).find(|&next| !lines[next].blank) {
+                forced[next] = true;
+            }
+        }
+
+        let mut out = String::new();
+        let mut blank = false;
+        let mut prev: Option<&Line> = None;
+        for (i, line) in lines.iter().enumerate() {
+            if line.blank {
+                blank = true;
+                continue;
+            }
+
+            if let Some(prev) = prev && (blank || forced[i]) && !prev.opens && !line.closes {
+                out.push('\n');
+            }
+            for _ in 0..line.indent {
+                out.push_str(INDENT);
+            }
+            out.push_str(&line.text);
+            out.push('\n');
+
+            blank = false;
+            prev = Some(line);
+        }
+        return out;
+    }
+}
+
+/// Whether a line ending in this operator carries on onto the next one
+fn continues_after(kind: &Tk) -> bool {
+    return Operator::binary(kind).is_some() || Operator::assignment(kind).is_some();
+}
@@ -1,10 +1,16 @@
-use crate::{ common::errors::{ Error, ErrorBuffer, ErrorKind, ErrorWriter }, throw };
+use crate::{ common::{ errors::{ Error, ErrorBuffer, ErrorKind, ErrorWriter }, trace::Tracer }, throw };
 use super::token::{ Tk, Token };
 
 pub struct Lexer<'a> {
-    source: &'a String,
+    source: &'a str,
     errors: ErrorBuffer,
     pos: usize,
+
+    /// Whether comments and whitespace are kept as tokens, see [`Lexer::with_trivia`]
+    trivia: bool,
+
+    /// Where every token is written as it is produced, if tracing is on
+    tracer: Option<Tracer>,
 }
 
 impl<'a> ErrorWriter for Lexer<'a> {
@@ -23,14 +29,30 @@ impl<'a> ErrorWriter for Lexer<'a> {
 
 impl<'a> Lexer<'a> {
     /// Initializes a new lexer with the given source
-    pub fn new(source: &'a String) -> Lexer<'a> {
+    pub fn new(source: &'a str) -> Lexer<'a> {
         Lexer {
             source,
             errors: vec![],
             pos: 0,
+            trivia: false,
+            tracer: None,
         }
     }
 
+    /// Initializes a lexer that also produces comment and whitespace tokens, so the
+    /// spans of the tokens cover the whole source. These tokens have to be filtered
+    /// out before the stream is handed to the parser.
+    pub fn with_trivia(source: &'a str) -> Lexer<'a> {
+        let mut lexer = Lexer::new(source);
+        lexer.trivia = true;
+        return lexer;
+    }
+
+    /// Writes the tokens to `tracer` once they are lexed
+    pub fn trace(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
     /// Takes the input given to the lexer and iterates through, creating tokens
     /// and eventually returning them as a vector
     pub fn lex(&mut self) -> (Vec<Token>, ErrorBuffer) {
@@ -41,112 +63,124 @@ impl<'a> Lexer<'a> {
 
             match ch {
                 // whitespace ignore
-                ' ' | '\t' | '\r' => {}
-                '\n' => tokens.push(Token::new(Tk::Newline, start..start, "\\n")),
+                ' ' | '\t' | '\r' => {
+                    while let Some(' ' | '\t' | '\r') = self.peek() {
+                        self.advance();
+                    }
+                    if self.trivia {
+                        let lexeme = &self.source[start..self.end()];
+                        tokens.push(Token::new(Tk::Whitespace, start..self.end(), lexeme));
+                    }
+                }
+                '\n' => tokens.push(Token::new(Tk::Newline, start..start + 1, "\\n")),
 
                 // grouping operators
-                '(' => tokens.push(Token::new(Tk::LParen, start..start, "(")),
-                ')' => tokens.push(Token::new(Tk::RParen, start..start, ")")),
-                '[' => tokens.push(Token::new(Tk::LBrac, start..start, "[")),
-                ']' => tokens.push(Token::new(Tk::RBrac, start..start, "]")),
-                '{' => tokens.push(Token::new(Tk::LCurl, start..start, "{")),
-                '}' => tokens.push(Token::new(Tk::RCurl, start..start, "}")),
-
-                // tokenize ellipsis
+                '(' => tokens.push(Token::new(Tk::LParen, start..start + 1, "(")),
+                ')' => tokens.push(Token::new(Tk::RParen, start..start + 1, ")")),
+                '[' => tokens.push(Token::new(Tk::LBrac, start..start + 1, "[")),
+                ']' => tokens.push(Token::new(Tk::RBrac, start..start + 1, "]")),
+                '{' => tokens.push(Token::new(Tk::LCurl, start..start + 1, "{")),
+                '}' => tokens.push(Token::new(Tk::RCurl, start..start + 1, "}")),
+
+                // tokenize ranges and ellipsis
                 '.' => if self.expect('.') {
                     if self.expect('.') {
-                        tokens.push(Token::new(Tk::Ellipsis, start..self.pos, "..."));
+                        tokens.push(Token::new(Tk::Ellipsis, start..self.end(), "..."));
+                    } else if self.expect('=') {
+                        tokens.push(Token::new(Tk::DotDotEqual, start..self.end(), "..="));
                     } else {
-                        self.pos -= 1; // go back one
-                        tokens.push(Token::new(Tk::Dot, start..self.pos, "."));
+                        tokens.push(Token::new(Tk::DotDot, start..self.end(), ".."));
                     }
                 } else {
-                    tokens.push(Token::new(Tk::Dot, start..self.pos, "."));
+                    tokens.push(Token::new(Tk::Dot, start..self.end(), "."));
                 }
 
                 // double wide arithmetic operators
                 '+' => if self.expect('+') {
-                    tokens.push(Token::new(Tk::PlusPlus, start..self.pos, "++"));
+                    tokens.push(Token::new(Tk::PlusPlus, start..self.end(), "++"));
                 } else if self.expect('=') {
-                    tokens.push(Token::new(Tk::PlusEqual, start..self.pos, "+="));
+                    tokens.push(Token::new(Tk::PlusEqual, start..self.end(), "+="));
                 } else {
-                    tokens.push(Token::new(Tk::Plus, start..self.pos, "+"));
+                    tokens.push(Token::new(Tk::Plus, start..self.end(), "+"));
                 }
 
                 '-' => if self.expect('-') {
-                    tokens.push(Token::new(Tk::MinusMinus, start..self.pos, "--"));
+                    tokens.push(Token::new(Tk::MinusMinus, start..self.end(), "--"));
                 } else if self.expect('=') {
-                    tokens.push(Token::new(Tk::MinusEqual, start..self.pos, "-="));
+                    tokens.push(Token::new(Tk::MinusEqual, start..self.end(), "-="));
                 } else if self.expect('>') {
-                    tokens.push(Token::new(Tk::RArrow, start..self.pos, "->"));
+                    tokens.push(Token::new(Tk::RArrow, start..self.end(), "->"));
                 } else {
-                    tokens.push(Token::new(Tk::Minus, start..self.pos, "-"));
+                    tokens.push(Token::new(Tk::Minus, start..self.end(), "-"));
                 }
 
                 // triple wide arithmetic operators
                 '*' => if self.expect('*') {
                     if self.expect('=') {
-                        tokens.push(Token::new(Tk::StarStarEqual, start..self.pos, "**="));
+                        tokens.push(Token::new(Tk::StarStarEqual, start..self.end(), "**="));
                     } else {
-                        tokens.push(Token::new(Tk::StarStar, start..self.pos, "**"));
+                        tokens.push(Token::new(Tk::StarStar, start..self.end(), "**"));
                     }
                 } else if self.expect('=') {
-                    tokens.push(Token::new(Tk::StarEqual, start..self.pos, "*="));
+                    tokens.push(Token::new(Tk::StarEqual, start..self.end(), "*="));
                 } else {
-                    tokens.push(Token::new(Tk::Star, start..self.pos, "*"));
+                    tokens.push(Token::new(Tk::Star, start..self.end(), "*"));
                 }
 
                 '/' => if self.expect('/') {
                     if self.expect('=') {
-                        tokens.push(Token::new(Tk::SlashSlashEqual, start..self.pos, "//="));
+                        tokens.push(Token::new(Tk::SlashSlashEqual, start..self.end(), "//="));
                     } else {
-                        tokens.push(Token::new(Tk::SlashSlash, start..self.pos, "//"));
+                        tokens.push(Token::new(Tk::SlashSlash, start..self.end(), "//"));
                     }
                 } else if self.expect('=') {
-                    tokens.push(Token::new(Tk::SlashEqual, start..self.pos, "/="));
+                    tokens.push(Token::new(Tk::SlashEqual, start..self.end(), "/="));
                 } else {
-                    tokens.push(Token::new(Tk::Slash, start..self.pos, "/"));
+                    tokens.push(Token::new(Tk::Slash, start..self.end(), "/"));
                 }
 
                 // comparison operators
                 '<' => if self.expect('=') {
-                    tokens.push(Token::new(Tk::LessEqual, start..self.pos, "<="));
+                    tokens.push(Token::new(Tk::LessEqual, start..self.end(), "<="));
                 } else {
-                    tokens.push(Token::new(Tk::Less, start..self.pos, "<"));
+                    tokens.push(Token::new(Tk::Less, start..self.end(), "<"));
                 }
                 '>' => if self.expect('=') {
-                    tokens.push(Token::new(Tk::MoreEqual, start..self.pos, ">="));
+                    tokens.push(Token::new(Tk::MoreEqual, start..self.end(), ">="));
                 } else {
-                    tokens.push(Token::new(Tk::More, start..self.pos, ">"));
+                    tokens.push(Token::new(Tk::More, start..self.end(), ">"));
                 }
                 '=' => if self.expect('=') {
-                    tokens.push(Token::new(Tk::EqualEqual, start..self.pos, "=="));
+                    tokens.push(Token::new(Tk::EqualEqual, start..self.end(), "=="));
                 } else {
-                    tokens.push(Token::new(Tk::Equal, start..self.pos, "="));
+                    tokens.push(Token::new(Tk::Equal, start..self.end(), "="));
                 }
                 '!' => if self.expect('=') {
-                    tokens.push(Token::new(Tk::BangEqual, start..self.pos, "!="));
+                    tokens.push(Token::new(Tk::BangEqual, start..self.end(), "!="));
                 } else {
-                    tokens.push(Token::new(Tk::Bang, start..self.pos, "!"));
+                    tokens.push(Token::new(Tk::Bang, start..self.end(), "!"));
                 }
 
                 // logical operators
                 '|' => if self.expect('|') {
-                    tokens.push(Token::new(Tk::PipePipe, start..self.pos, "||"));
+                    tokens.push(Token::new(Tk::PipePipe, start..self.end(), "||"));
+                } else if self.expect('>') {
+                    tokens.push(Token::new(Tk::PipeMore, start..self.end(), "|>"));
                 } else {
-                    tokens.push(Token::new(Tk::Pipe, start..self.pos, "|"));
+                    tokens.push(Token::new(Tk::Pipe, start..self.end(), "|"));
                 }
                 '&' => if self.expect('&') {
-                    tokens.push(Token::new(Tk::Amprsnd, start..self.pos, "&&"));
+                    tokens.push(Token::new(Tk::AmprsndAmprsnd, start..self.end(), "&&"));
                 } else {
-                    tokens.push(Token::new(Tk::AmprsndAmprsnd, start..self.pos, "&"));
+                    tokens.push(Token::new(Tk::Amprsnd, start..self.end(), "&"));
                 }
 
-                '%' => tokens.push(Token::new(Tk::Modulo, start..start, "%")),
-                ':' => tokens.push(Token::new(Tk::Colon, start..start, ":")),
-                ';' => tokens.push(Token::new(Tk::Semicolon, start..start, ";")),
-                ',' => tokens.push(Token::new(Tk::Comma, start..start, ",")),
-                '$' => tokens.push(Token::new(Tk::Sigil, start..start, "$")),
+                '%' => tokens.push(Token::new(Tk::Modulo, start..start + 1, "%")),
+                ':' => tokens.push(Token::new(Tk::Colon, start..start + 1, ":")),
+                ';' => tokens.push(Token::new(Tk::Semicolon, start..start + 1, ";")),
+                ',' => tokens.push(Token::new(Tk::Comma, start..start + 1, ",")),
+                '$' => tokens.push(Token::new(Tk::Sigil, start..start + 1, "$")),
+                '?' => tokens.push(Token::new(Tk::Question, start..start + 1, "?")),
 
                 '"' => {
                     let mut lexeme = String::new();
@@ -156,7 +190,10 @@ impl<'a> Lexer<'a> {
                         match self.peek() {
                             // if a valid cahracter comes next
                             Some(next_ch) => if next_ch == '\\' {
-                                unimplemented!("Escape sequences not support yet brochacho... </3");
+                                self.advance();
+                                if let Some(escaped) = self.escape() {
+                                    lexeme.push(escaped);
+                                }
                             } else if next_ch == '"' {
                                 self.advance();
                                 break 'string;
@@ -170,7 +207,7 @@ impl<'a> Lexer<'a> {
                                 self.error(
                                     throw!(
                                         SyntaxError,
-                                        start..self.pos,
+                                        start..self.end(),
                                         "string literal is missing a closing '\"'"
                                     )
                                 );
@@ -181,7 +218,7 @@ impl<'a> Lexer<'a> {
 
                     tokens.push(Token {
                         kind: Tk::String,
-                        span: start..self.pos,
+                        span: start..self.end(),
                         lexeme,
                     });
                 }
@@ -199,7 +236,7 @@ impl<'a> Lexer<'a> {
                     }
 
                     let kind = Tk::from_lexeme(&lexeme);
-                    tokens.push(Token { kind, span: start..self.pos, lexeme });
+                    tokens.push(Token { kind, span: start..self.end(), lexeme });
                 }
 
                 '0'..='9' => {
@@ -220,13 +257,10 @@ impl<'a> Lexer<'a> {
 
                             self.advance();
                             if let Some(after_decimal) = self.peek() {
-                                if
-                                    !after_decimal.is_ascii_digit() &&
-                                    after_decimal != '_' &&
-                                    after_decimal != '.'
-                                {
+                                // a second dot makes `1..5` a range instead of the float `1.`
+                                if !after_decimal.is_ascii_digit() && after_decimal != '_' {
                                     self.pos -= 1;
-                                    break; // this is not a decimal part of the number, it's index into integer
+                                    break; // this is not a decimal part of the number, it's index into integer or range
                                 } else {
                                     lexeme.push('.');
                                     kind = Tk::Float; // yeah ill have the regular please
@@ -241,19 +275,21 @@ impl<'a> Lexer<'a> {
                         self.advance();
                         lexeme.push(next_ch);
                     }
-                    tokens.push(Token { kind, span: start..self.pos, lexeme });
+                    tokens.push(Token { kind, span: start..self.end(), lexeme });
                 }
+                // comments run up to the newline, which is still its own token
                 '#' => {
-                    while let Some(next_ch) = self.peek() {
+                    while self.peek().is_some_and(|next_ch| next_ch != '\n') {
                         self.advance();
-                        if next_ch == '\n' {
-                            break;
-                        }
+                    }
+                    if self.trivia {
+                        let lexeme = &self.source[start..self.end()];
+                        tokens.push(Token::new(Tk::Comment, start..self.end(), lexeme));
                     }
                 }
                 _ =>
                     self.error(
-                        throw!(IllegalCharacter, start..self.pos, "this character is not allowed")
+                        throw!(IllegalCharacter, start..self.end(), "this character is not allowed")
                     ),
             }
             self.advance();
@@ -261,6 +297,9 @@ impl<'a> Lexer<'a> {
 
         // sneak a little EOF to cap off the token stream
         tokens.push(Token::eof(self.pos..self.pos));
+        if let Some(tracer) = &self.tracer {
+            tokens.iter().for_each(|token| tracer.token(token));
+        }
         return (tokens, self.dump_errors());
     }
 }
@@ -268,36 +307,59 @@ impl<'a> Lexer<'a> {
 impl<'a> Lexer<'a> {
     /// Returns the character at current position <=> `pos` is not the end
     fn current(&self) -> Option<char> {
-        if self.source.len() > self.pos {
-            return Some(self.source.as_bytes()[self.pos] as char);
-        }
-        return None;
+        return self.source[self.pos..].chars().next();
     }
 
-    /// Returns the character 1 position ahead of the current position <=> `pos+1` is not the end
+    /// Returns the character after the one at the current position, if there is one
     fn peek(&self) -> Option<char> {
-        if self.source.len() > self.pos + 1 {
-            return Some(self.source.as_bytes()[self.pos + 1] as char);
-        }
-        return None;
+        return self.source[self.end()..].chars().next();
+    }
+
+    /// Where the character at the current position ends, characters outside of
+    /// ASCII take up more than one byte
+    fn end(&self) -> usize {
+        return self.pos + self.current().map_or(1, char::len_utf8);
     }
 
     /// Peeks ahead one and returns whether or not the next character equals
     /// the one provided `next_ch`. Also advances if the expected char was found.
     fn expect(&mut self, next_ch: char) -> bool {
-        if let Some(ch) = self.peek() {
-            if ch == next_ch {
-                self.advance();
-                return true;
-            }
+        if self.peek() == Some(next_ch) {
+            self.advance();
+            return true;
         }
         return false;
     }
 
-    /// Moves the position of the lexer ahead by one <=> it isn't at the end of the stream
+    /// Reads the character after a backslash inside of a string literal and returns
+    /// what it stands for. Unknown escapes are reported and dropped from the lexeme.
+    fn escape(&mut self) -> Option<char> {
+        let start = self.pos;
+        let escaped = match self.peek() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('$') => '$',
+            Some(_) => {
+                self.advance();
+                self.error(throw!(SyntaxError, start..self.end(), "unknown escape sequence"));
+                return None;
+            }
+            None => {
+                return None; // unterminated string, reported by the caller
+            }
+        };
+        self.advance();
+        return Some(escaped);
+    }
+
+    /// Moves the position of the lexer ahead by one character <=> it isn't at the end of the stream
     fn advance(&mut self) {
         if self.source.len() > self.pos {
-            self.pos += 1;
+            self.pos = self.end();
         }
     }
 }
@@ -81,6 +81,7 @@ pub enum TokenKind {
     // logical operators
     Pipe,
     PipePipe,
+    PipeMore,
     Amprsnd,
     AmprsndAmprsnd,
 
@@ -90,9 +91,18 @@ pub enum TokenKind {
     Semicolon,
     Comma,
     Dot,
+    DotDot,
+    DotDotEqual,
     Newline,
     Sigil,
     Ellipsis,
+    Question,
+
+    // trivia, only produced when the lexer is asked to keep it
+    Comment,
+    Whitespace,
+    /// Source text the lexer rejected, which syntax trees keep to stay lossless
+    Unknown,
 
     // literals
     True,
@@ -107,8 +117,10 @@ pub enum TokenKind {
     Function,
     Return,
     If,
+    Then,
     Else,
     For,
+    Do,
     While,
     Break,
     Class,
@@ -121,23 +133,52 @@ pub enum TokenKind {
     From,
     As,
     Inline,
+    Static,
     Pub,
+    Match,
+    Case,
     In,
+    Default,
 }
 
 impl TokenKind {
+    /// Whether or not this token can only appear at the start of a statement, which
+    /// makes it a safe place for the parser to resynchronize after an error.
+    pub fn starts_stmt(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Let |
+                TokenKind::Function |
+                TokenKind::Return |
+                TokenKind::If |
+                TokenKind::Match |
+                TokenKind::For |
+                TokenKind::While |
+                TokenKind::Break |
+                TokenKind::Class |
+                TokenKind::Enum |
+                TokenKind::Import |
+                TokenKind::From |
+                TokenKind::Inline |
+                TokenKind::Static |
+                TokenKind::Pub
+        )
+    }
+
     /// Takes a lexeme and eithe returns the keyword corresponding with the lexeme or
     /// identifier in the case that the lexeme has no token kind.
-    pub fn from_lexeme(lexeme: &String) -> TokenKind {
-        match lexeme.as_str() {
+    pub fn from_lexeme(lexeme: &str) -> TokenKind {
+        match lexeme {
             "let" => TokenKind::Let,
             "function" => TokenKind::Function,
             "return" => TokenKind::Return,
             "true" => TokenKind::True,
             "false" => TokenKind::False,
             "if" => TokenKind::If,
+            "then" => TokenKind::Then,
             "else" => TokenKind::Else,
             "for" => TokenKind::For,
+            "do" => TokenKind::Do,
             "while" => TokenKind::While,
             "break" => TokenKind::Break,
             "class" => TokenKind::Class,
@@ -150,8 +191,12 @@ impl TokenKind {
             "from" => TokenKind::From,
             "as" => TokenKind::As,
             "inline" => TokenKind::Inline,
+            "static" => TokenKind::Static,
+            "match" => TokenKind::Match,
+            "case" => TokenKind::Case,
             "pub" => TokenKind::Pub,
             "in" => TokenKind::In,
+            "default" => TokenKind::Default,
             _ => TokenKind::Ident,
         }
     }
@@ -0,0 +1,65 @@
+// explicit returns and `module/module.rs` layouts are the house style
+#![allow(clippy::needless_return, clippy::module_inception)]
+
+use analysis::{ initializers, variants };
+use common::{ errors::ErrorBuffer, trace::Tracer };
+use lexer::{ lexer::Lexer, token::Token };
+use parser::{ ast::Stmt, parser::Parser };
+
+pub mod common;
+pub mod lexer;
+pub mod parser;
+pub mod analysis;
+pub mod emit;
+pub mod format;
+pub mod syntax;
+mod tests;
+
+pub fn lex(source_code: &str) -> (Vec<Token>, ErrorBuffer) {
+    let mut lexer = Lexer::new(source_code);
+    return lexer.lex();
+}
+
+pub fn parse(source: Vec<Token>) -> (Vec<Stmt>, ErrorBuffer) {
+    let mut parser = Parser::new(source);
+    return parser.parse();
+}
+
+/// What `dump` renders, picked with `kaska --emit=...`
+pub enum Emit {
+    Tokens,
+    Ast,
+    Sexp,
+    Dot,
+}
+
+/// Runs the whole front end over `source_code`, lexing, parsing and resolving it,
+/// and renders the tokens or the tree as `emit` asks. The lexer and parser write to
+/// `tracer` if there is one.
+pub fn dump(source_code: &str, emit: Emit, tracer: Option<Tracer>) -> (String, ErrorBuffer) {
+    let mut lexer = Lexer::new(source_code);
+    if let Some(tracer) = &tracer {
+        lexer.trace(tracer.clone());
+    }
+    let (tokens, mut errors) = lexer.lex();
+    let mut output = String::new();
+    if let Emit::Tokens = emit {
+        output = emit::json::tokens(&tokens);
+    }
+
+    let mut parser = Parser::new(tokens);
+    if let Some(tracer) = tracer {
+        parser.trace(tracer);
+    }
+    let (mut ast, parse_errs) = parser.parse();
+    errors.extend(parse_errs);
+    errors.extend(variants::resolve(&mut ast));
+    errors.extend(initializers::resolve(&mut ast));
+    match emit {
+        Emit::Tokens => {}
+        Emit::Ast => output = emit::json::ast(&ast),
+        Emit::Sexp => output = emit::sexp::ast(&ast),
+        Emit::Dot => output = emit::dot::ast(&ast),
+    }
+    return (output, errors);
+}
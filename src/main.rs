@@ -1,39 +1,112 @@
-use std::fs;
-use common::errors::ErrorBuffer;
-use lexer::{ lexer::Lexer, token::Token };
-use parser::{ ast::Stmt, parser::Parser };
-
-mod common;
-mod lexer;
-mod parser;
-mod analysis;
-mod tests;
-
-const PATH: &'static str = "main.kas";
-
-fn lex(source_code: &String) -> (Vec<Token>, ErrorBuffer) {
-    let mut lexer = Lexer::new(&source_code);
-    return lexer.lex();
-}
+// explicit returns are the house style
+#![allow(clippy::needless_return)]
 
-pub fn parse(source: Vec<Token>) -> (Vec<Stmt>, ErrorBuffer) {
-    let mut parser = Parser::new(source);
-    return parser.parse();
-}
+use std::{ env, fs, process };
+use kaska::{ common::trace::{ Tracer, TRACE_VAR }, format, Emit };
+
+const PATH: &str = "main.kas";
 
 fn main() {
-    let source_code = fs::read_to_string(&PATH).expect("There was an error reading the file!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "fmt") {
+        process::exit(fmt(&args[1..]));
+    }
+
+    process::exit(dump(&args));
+}
 
-    // tokenize and debug
-    let (tokens, lex_errs) = lex(&source_code);
-    println!("Tokens:\n{:#?}", tokens);
+/// Lexes and parses a file and dumps the result, the tree as JSON unless `--emit`
+/// asks for the tokens, S-expressions or a Graphviz graph instead. Errors go to
+/// stderr. `--trace` or `--trace=file`, or the `KASKA_TRACE` variable, also traces
+/// the lexer and parser to stderr or the file. Returns the exit code.
+fn dump(args: &[String]) -> i32 {
+    let mut emit = Emit::Ast;
+    let mut path = PATH;
+    let mut trace = env::var(TRACE_VAR).ok();
+    for arg in args {
+        if arg == "--trace" {
+            trace = Some("stderr".to_string());
+            continue;
+        }
+        if let Some(target) = arg.strip_prefix("--trace=") {
+            trace = Some(target.to_string());
+            continue;
+        }
+
+        match arg.strip_prefix("--emit=") {
+            Some("tokens") => emit = Emit::Tokens,
+            Some("ast") => emit = Emit::Ast,
+            Some("sexp") => emit = Emit::Sexp,
+            Some("dot") => emit = Emit::Dot,
+            Some(other) => {
+                eprintln!("unknown --emit kind '{other}', expected tokens, ast, sexp or dot");
+                return 2;
+            }
+            None => path = arg,
+        }
+    }
+
+    let tracer = match trace.as_deref().map(Tracer::open) {
+        None => None,
+        Some(Ok(tracer)) => tracer,
+        Some(Err(err)) => {
+            eprintln!("{}: could not be opened for tracing, {err}", trace.unwrap_or_default());
+            return 1;
+        }
+    };
+
+    let Ok(source_code) = fs::read_to_string(path) else {
+        eprintln!("{path}: could not be read");
+        return 1;
+    };
+
+    let (output, errors) = kaska::dump(&source_code, emit, tracer);
+    print!("{output}");
+    if errors.is_empty() {
+        return 0;
+    }
+    eprintln!("{:#?}", errors);
+    return 1;
+}
 
-    // parse and debug
-    let (ast, parse_errs) = parse(tokens);
-    println!("AST:\n{:#?}", ast);
+/// `kaska fmt [--check] [files...]` rewrites the files in their canonical layout,
+/// or with `--check` only lists the ones that aren't. Returns the exit code.
+fn fmt(args: &[String]) -> i32 {
+    let check = args.iter().any(|arg| arg == "--check");
+    let mut paths: Vec<&str> = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .map(String::as_str)
+        .collect();
+    if paths.is_empty() {
+        paths.push(PATH);
+    }
 
-    // print errors
-    println!("Errors:");
-    println!("{:#?}", lex_errs);
-    println!("{:#?}", parse_errs);
+    let mut code = 0;
+    for path in paths {
+        let Ok(source) = fs::read_to_string(path) else {
+            eprintln!("{path}: could not be read");
+            code = 1;
+            continue;
+        };
+        match format::formatter::format(&source) {
+            Err(errors) => {
+                eprintln!("{path}: not formatted because of errors");
+                eprintln!("{:#?}", errors);
+                code = 1;
+            }
+            Ok(formatted) if formatted == source => {}
+            Ok(_) if check => {
+                println!("{path}: not formatted");
+                code = 1;
+            }
+            Ok(formatted) => {
+                if fs::write(path, formatted).is_err() {
+                    eprintln!("{path}: could not be written");
+                    code = 1;
+                }
+            }
+        }
+    }
+    return code;
 }
@@ -1,6 +1,28 @@
 use crate::{ common::span::Span, lexer::token::Tk };
 use std::fmt::Display;
 
+// ----------------------------------------------------------------- \\
+// NODE IDS
+// ----------------------------------------------------------------- \\
+
+/// Identifies a node of the tree, handed out by the parser in the order nodes are
+/// finished. Ids are unique but can have gaps, since recovery and later passes
+/// drop nodes along with their ids.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct NodeId(pub usize);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        return self.0;
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
 // ----------------------------------------------------------------- \\
 // EXPRESSIONS
 // ----------------------------------------------------------------- \\
@@ -9,11 +31,11 @@ use std::fmt::Display;
 pub struct Expr {
     pub kind: ExprKind,
     pub span: Span,
-    pub uid: usize,
+    pub uid: NodeId,
 }
 
 impl Expr {
-    pub fn new(uid: usize, kind: ExprKind, span: Span) -> Expr {
+    pub fn new(uid: NodeId, kind: ExprKind, span: Span) -> Expr {
         Expr { uid, kind, span }
     }
 }
@@ -37,11 +59,54 @@ pub enum ExprKind {
         name: String,
     },
 
+    ArrayLiteral {
+        elements: Vec<Expr>,
+    },
+    /// `[value; count]`
+    ArrayRepeat {
+        value: Box<Expr>,
+        count: Box<Expr>,
+    },
+    SeqLiteral {
+        elements: Vec<Expr>,
+    },
+    /// `(a, b)`, `(a,)` or `()`, a single value in parentheses is just that value
+    Tuple {
+        elements: Vec<Expr>,
+    },
+
     // compound expressions
     Call {
         callee: Box<Expr>,
         args: Vec<Expr>,
     },
+    Field {
+        object: Box<Expr>,
+        name: String,
+    },
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+    },
+    /// `a..b` or `a..=b`, the bounds can only be left out when slicing like `seq[..3]`
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+    },
+    /// Constructs an enum value, `Name.Variant` or `Name.Variant(args)`. The parser
+    /// reads these as a field or call, analysis turns them into variants once it
+    /// knows that `Name` is an enum.
+    Variant {
+        enum_name: String,
+        variant: String,
+        args: Vec<Expr>,
+    },
+    /// Applies type arguments to a name, like `Point{T}` or `int{}`
+    TypeApply {
+        callee: Box<Expr>,
+        args: Vec<TypeExpr>,
+    },
     Assignment {
         assignee: Box<Expr>,
         value: Box<Expr>,
@@ -49,7 +114,12 @@ pub enum ExprKind {
     },
     Parameter {
         name: String,
-        ty: Box<Expr>,
+        ty: Option<Box<TypeExpr>>,
+    },
+    Lambda {
+        params: Vec<Expr>,
+        ret: Option<TypeExpr>,
+        body: Vec<Stmt>,
     },
 
     // operator/operand expressions
@@ -58,24 +128,189 @@ pub enum ExprKind {
         rhs: Box<Expr>,
         op: Operator,
     },
+    /// `value is T` or `value is not T`, where `T` may be `None`
+    Is {
+        value: Box<Expr>,
+        ty: TypeExpr,
+        negated: bool,
+    },
+
+    /// `default`, the zero value of whatever type is being initialized. Only valid
+    /// as the whole value of an annotated `let` or a field initializer, and
+    /// replaced by the actual zero value during analysis.
+    Default,
+
+    /// Placeholder for an expression that failed to parse, the error
+    /// itself has already been recorded by the parser
+    Error,
 }
 
 #[macro_export]
 macro_rules! expr {
+    (ArrayLiteral, $uid:expr, $elements:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::ArrayLiteral { elements: $elements }, $span)
+    };
+    (ArrayRepeat, $uid:expr, $value:expr, $count:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::ArrayRepeat { value: Box::new($value), count: Box::new($count) }, $span)
+    };
+    (SeqLiteral, $uid:expr, $elements:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::SeqLiteral { elements: $elements }, $span)
+    };
+    (Tuple, $uid:expr, $elements:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::Tuple { elements: $elements }, $span)
+    };
     (Call, $uid:expr, $callee:expr, $args:expr, $span:expr) => {
         Expr::new($uid, ExprKind::Call { callee: Box::new($callee), args: $args }, $span)
     };
+    (Field, $uid:expr, $object:expr, $name:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::Field { object: Box::new($object), name: $name }, $span)
+    };
+    (Index, $uid:expr, $object:expr, $index:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::Index { object: Box::new($object), index: Box::new($index) }, $span)
+    };
+    (Range, $uid:expr, $start:expr, $end:expr, $inclusive:expr, $span:expr) => {
+        Expr::new(
+            $uid,
+            ExprKind::Range { start: $start.map(Box::new), end: $end.map(Box::new), inclusive: $inclusive },
+            $span
+        )
+    };
+    (TypeApply, $uid:expr, $callee:expr, $args:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::TypeApply { callee: Box::new($callee), args: $args }, $span)
+    };
     (Binary, $uid:expr, $lhs:expr, $rhs:expr, $op:expr, $span:expr) => {
         Expr::new($uid, ExprKind::Binary { lhs: Box::new($lhs), rhs: Box::new($rhs), op: $op }, $span)
     };
+    (Is, $uid:expr, $value:expr, $ty:expr, $negated:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::Is { value: Box::new($value), ty: $ty, negated: $negated }, $span)
+    };
     (Assignment, $uid:expr, $assignee:expr, $value:expr, $op:expr, $span:expr) => {
         Expr::new($uid, ExprKind::Assignment { assignee: Box::new($assignee), value: Box::new($value), op: $op }, $span)
     };
     (Parameter, $uid:expr, $name:expr, $ty:expr, $span:expr) => {
-        Expr::new($uid, ExprKind::Parameter { name: $name, ty: Box::new($ty) }, $span)
+        Expr::new($uid, ExprKind::Parameter { name: $name, ty: $ty.map(Box::new) }, $span)
+    };
+    (Lambda, $uid:expr, $params:expr, $ret:expr, $body:expr, $span:expr) => {
+        Expr::new($uid, ExprKind::Lambda { params: $params, ret: $ret, body: $body }, $span)
     };
 }
 
+// ----------------------------------------------------------------- \\
+// TYPE EXPRESSIONS
+// ----------------------------------------------------------------- \\
+
+/// A type as it is written in the source, such as an annotation or return type.
+/// These are kept apart from value expressions since they have their own grammar.
+#[derive(Debug)]
+pub struct TypeExpr {
+    pub kind: TypeKind,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+impl TypeExpr {
+    pub fn new(uid: NodeId, kind: TypeKind, span: Span) -> TypeExpr {
+        TypeExpr { uid, kind, span }
+    }
+}
+
+#[derive(Debug)]
+pub enum TypeKind {
+    /// `int`, `T`, `Point`
+    Named {
+        name: String,
+    },
+    /// `Point{T}` or `Point<T>`
+    Generic {
+        name: String,
+        args: Vec<TypeExpr>,
+    },
+    /// `int?`
+    Nullable {
+        inner: Box<TypeExpr>,
+    },
+    /// `[int; 3]`
+    Array {
+        elem: Box<TypeExpr>,
+        size: Box<Expr>,
+    },
+    /// `int{}`
+    Sequence {
+        elem: Box<TypeExpr>,
+    },
+    /// `(int, str)`
+    Tuple {
+        elems: Vec<TypeExpr>,
+    },
+    /// `function(int, str) -> bool`
+    Function {
+        params: Vec<TypeExpr>,
+        ret: Option<Box<TypeExpr>>,
+    },
+
+    /// Placeholder for a type that failed to parse, the error
+    /// itself has already been recorded by the parser
+    Error,
+}
+
+// ----------------------------------------------------------------- \\
+// PATTERNS
+// ----------------------------------------------------------------- \\
+
+/// The left hand side of a match arm, tested against a value and binding
+/// names to its parts if it matches. Also used for the names bound by `let`.
+#[derive(Debug)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+impl Pattern {
+    pub fn new(uid: NodeId, kind: PatternKind, span: Span) -> Pattern {
+        Pattern { uid, kind, span }
+    }
+
+    /// Whether the pattern matches every value
+    pub fn is_irrefutable(&self) -> bool {
+        return match &self.kind {
+            PatternKind::Wildcard | PatternKind::Binding { .. } => true,
+            PatternKind::Tuple { elements } => elements.iter().all(|element| element.is_irrefutable()),
+            _ => false,
+        };
+    }
+}
+
+#[derive(Debug)]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    /// `None`
+    None,
+    /// A plain name, which matches anything and binds it
+    Binding {
+        name: String,
+    },
+    /// An integer, float, string or boolean literal
+    Literal {
+        value: Expr,
+    },
+    /// `(a, _)`
+    Tuple {
+        elements: Vec<Pattern>,
+    },
+    /// `Shape.Circle(r)`, with one pattern per payload value
+    Variant {
+        enum_name: String,
+        variant: String,
+        fields: Vec<Pattern>,
+    },
+
+    /// Placeholder for a pattern that failed to parse, the error
+    /// itself has already been recorded by the parser
+    Error,
+}
+
 // ----------------------------------------------------------------- \\
 // STATEMENTS
 // ----------------------------------------------------------------- \\
@@ -84,41 +319,259 @@ macro_rules! expr {
 pub struct Stmt {
     pub kind: StmtKind,
     pub span: Span,
-    pub uid: usize,
+    pub uid: NodeId,
 }
 
 impl Stmt {
-    pub fn new(uid: usize, kind: StmtKind, span: Span) -> Stmt {
+    pub fn new(uid: NodeId, kind: StmtKind, span: Span) -> Stmt {
         Stmt { uid, kind, span }
     }
 }
 
 #[derive(Debug)]
 pub enum StmtKind {
+    /// `let x = ...` or a destructuring `let (x, _) = ...`
     Variable {
-        name: String,
-        typ: Option<Expr>,
+        modifiers: Vec<Modifier>,
+        pattern: Pattern,
+        typ: Option<TypeExpr>,
         value: Expr,
     },
 
     Function {
+        modifiers: Vec<Modifier>,
         name: String,
-        ret: Option<Expr>,
+        type_params: Vec<TypeParam>,
         params: Vec<Expr>,
+        ret: Option<TypeExpr>,
+        where_clause: Vec<Constraint>,
         body: Vec<Stmt>,
     },
+
+    Class(ClassDecl),
+    Enum(EnumDecl),
+    Import(ImportDecl),
+
+    Return {
+        value: Option<Expr>,
+    },
+
+    /// `else if` branches are stored as an if statement making up all of `otherwise`
+    If {
+        cond: Expr,
+        then: Vec<Stmt>,
+        otherwise: Option<Vec<Stmt>>,
+    },
+
+    Match {
+        value: Expr,
+        arms: Vec<MatchArm>,
+    },
+
+    /// `for x in xs do ... end`, several names like `for i, s in seq` are bound as a tuple
+    For {
+        binding: Pattern,
+        iterable: Expr,
+        body: Vec<Stmt>,
+    },
+
+    Expression {
+        expr: Expr,
+    },
+
+    /// `let self.x = value`, initializing a field of the instance a constructor builds
+    FieldInit {
+        name: String,
+        value: Expr,
+    },
+
+    /// Placeholder for a statement that failed to parse, the error
+    /// itself has already been recorded by the parser
+    Error,
+}
+
+#[derive(Debug)]
+pub struct ClassDecl {
+    pub modifiers: Vec<Modifier>,
+    pub name: String,
+    pub type_params: Vec<TypeParam>,
+    pub where_clause: Vec<Constraint>,
+    pub fields: Vec<FieldDecl>,
+
+    /// Every method except the constructor, these are all `StmtKind::Function`
+    /// unless they failed to parse
+    pub methods: Vec<Stmt>,
+
+    /// The method named after the class, if it has one
+    pub constructor: Option<Box<Stmt>>,
+}
+
+#[derive(Debug)]
+pub struct FieldDecl {
+    pub name: String,
+    pub ty: TypeExpr,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+/// A type parameter of a function or class, either listed explicitly like
+/// `<T>` or declared implicitly by being constrained in a where clause
+#[derive(Debug)]
+pub struct TypeParam {
+    pub name: String,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+/// Binds a type parameter to a constraint, `T: A + B` makes one of these per bound
+#[derive(Debug)]
+pub struct Constraint {
+    pub param: String,
+    pub bound: String,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+#[derive(Debug)]
+pub struct EnumDecl {
+    pub modifiers: Vec<Modifier>,
+    pub name: String,
+    pub variants: Vec<VariantDecl>,
+}
+
+#[derive(Debug)]
+pub struct VariantDecl {
+    pub name: String,
+
+    /// The types carried by the variant, empty for unit variants
+    pub payload: Vec<TypeExpr>,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+/// `import a.b as c` or `from a.b import x, y as z end`
+#[derive(Debug)]
+pub struct ImportDecl {
+    /// The segments of the dotted module path
+    pub path: Vec<String>,
+    pub path_span: Span,
+
+    /// Name the module is bound to instead, `import Module as m`
+    pub alias: Option<String>,
+    pub imported: Imported,
+}
+
+#[derive(Debug)]
+pub enum Imported {
+    /// `import Module`, only the module itself is brought into scope
+    Module,
+    /// `from Module import * end`
+    Everything,
+    /// `from Module import a, b as c end`
+    Items(Vec<ImportItem>),
+}
+
+#[derive(Debug)]
+pub struct ImportItem {
+    pub name: String,
+    pub alias: Option<String>,
+
+    /// Covers just the imported name, not the alias
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+/// `case pattern if guard then ...`, running until the next `case` or `end`
+#[derive(Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+    pub uid: NodeId,
+}
+
+impl MatchArm {
+    /// Whether the arm matches every value, making the arms after it unreachable
+    pub fn is_catch_all(&self) -> bool {
+        return self.guard.is_none() && self.pattern.is_irrefutable();
+    }
 }
 
 #[macro_export]
 macro_rules! stmt {
-    (Variable, $uid:expr, $name:expr, $typ:expr, $value:expr, $span:expr) => {
-        Stmt::new($uid, StmtKind::Variable { name: $name, typ: $typ, value: $value }, $span)
+    (Variable, $uid:expr, $pattern:expr, $typ:expr, $value:expr, $span:expr) => {
+        Stmt::new($uid, StmtKind::Variable { modifiers: vec![], pattern: $pattern, typ: $typ, value: $value }, $span)
+    };
+    (FieldInit, $uid:expr, $name:expr, $value:expr, $span:expr) => {
+        Stmt::new($uid, StmtKind::FieldInit { name: $name, value: $value }, $span)
     };
-    (Function, $uid:expr, $name:expr, $ret:expr, $params:expr, $body:expr, $span:expr) => {
-        Stmt::new($uid, StmtKind::Function { name: $name, ret: $ret, params: $params, body: $body }, $span)
+    (Function, $uid:expr, $header:expr, $body:expr, $span:expr) => {
+        Stmt::new(
+            $uid,
+            StmtKind::Function {
+                modifiers: vec![],
+                name: $header.name,
+                type_params: $header.type_params,
+                params: $header.params,
+                ret: $header.ret,
+                where_clause: $header.where_clause,
+                body: $body,
+            },
+            $span
+        )
+    };
+    (If, $uid:expr, $cond:expr, $then:expr, $otherwise:expr, $span:expr) => {
+        Stmt::new($uid, StmtKind::If { cond: $cond, then: $then, otherwise: $otherwise }, $span)
+    };
+    (Expression, $uid:expr, $expr:expr, $span:expr) => {
+        Stmt::new($uid, StmtKind::Expression { expr: $expr }, $span)
     };
 }
 
+// ----------------------------------------------------------------- \\
+// MODIFIERS
+// ----------------------------------------------------------------- \\
+
+/// Keywords that can be written in front of a declaration
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Modifier {
+    Pub,
+    Inline,
+    Static,
+}
+
+impl Modifier {
+    pub fn from_token(tk: &Tk) -> Option<Modifier> {
+        match tk {
+            Tk::Pub => Some(Modifier::Pub),
+            Tk::Inline => Some(Modifier::Inline),
+            Tk::Static => Some(Modifier::Static),
+            _ => None,
+        }
+    }
+
+    /// Whether or not the modifier may be written in front of the declaration
+    /// starting with the given keyword
+    pub fn allowed_on(&self, tk: &Tk) -> bool {
+        match self {
+            Modifier::Pub => matches!(tk, Tk::Function | Tk::Class | Tk::Enum | Tk::Let),
+            Modifier::Inline => *tk == Tk::Function,
+            Modifier::Static => matches!(tk, Tk::Function | Tk::Let),
+        }
+    }
+}
+
+impl Display for Modifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Modifier::Pub => write!(f, "pub"),
+            Modifier::Inline => write!(f, "inline"),
+            Modifier::Static => write!(f, "static"),
+        }
+    }
+}
+
 // ----------------------------------------------------------------- \\
 // OPERATORS
 // ----------------------------------------------------------------- \\
@@ -132,6 +585,7 @@ pub enum Operator {
     Div,
     Exp,
     Floor,
+    Mod,
 
     // assignment operators
     Eq,
@@ -156,6 +610,9 @@ pub enum Operator {
     Bang,
     BangEq,
     EqEq,
+
+    // `a |> f(b)` is sugar for `f(a, b)`, see `Operator::precedence`
+    Pipe,
 }
 
 impl Operator {
@@ -167,23 +624,61 @@ impl Operator {
             Tk::Slash => Some(Operator::Div),
             Tk::StarStar => Some(Operator::Exp),
             Tk::SlashSlash => Some(Operator::Floor),
+            Tk::Modulo => Some(Operator::Mod),
 
             // logical operators
             Tk::PipePipe => Some(Operator::LogOr),
             Tk::AmprsndAmprsnd => Some(Operator::LogAnd),
+            Tk::Pipe => Some(Operator::BitOr),
+            Tk::Amprsnd => Some(Operator::BitAnd),
 
             // comparison operators
             Tk::Less => Some(Operator::Lt),
             Tk::LessEqual => Some(Operator::LtEq),
             Tk::More => Some(Operator::Mt),
             Tk::MoreEqual => Some(Operator::MtEq),
-            Tk::Bang => Some(Operator::Bang),
             Tk::BangEqual => Some(Operator::BangEq),
             Tk::EqualEqual => Some(Operator::EqEq),
+
+            Tk::PipeMore => Some(Operator::Pipe),
             _ => None,
         }
     }
 
+    /// How tightly a binary operator binds its operands, higher binds tighter.
+    /// Pipes bind the loosest of the binary operators, so `a + b |> f()` pipes the sum
+    /// and `x = a |> f()` assigns the result of the call.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Pipe => 1,
+            Operator::LogOr => 2,
+            Operator::LogAnd => 3,
+            Operator::BitOr => 4,
+            Operator::BitAnd => 5,
+            Operator::EqEq | Operator::BangEq => 6,
+            Operator::Lt | Operator::LtEq | Operator::Mt | Operator::MtEq => 7,
+            Operator::Add | Operator::Sub => 8,
+            Operator::Mul | Operator::Div | Operator::Floor | Operator::Mod => 9,
+            Operator::Exp => 10,
+
+            // these are never used as binary operators
+            Operator::Bang => 11,
+            Operator::Eq |
+            Operator::AddEq |
+            Operator::SubEq |
+            Operator::MulEq |
+            Operator::DivEq |
+            Operator::ExpEq |
+            Operator::FloorEq => 0,
+        }
+    }
+
+    /// Whether or not a chain of this operator groups from the right, `a ** b ** c`
+    /// is `a ** (b ** c)` while everything else groups from the left
+    pub fn right_assoc(&self) -> bool {
+        return *self == Operator::Exp;
+    }
+
     pub fn assignment(tk: &Tk) -> Option<Operator> {
         match tk {
             Tk::Equal => Some(Operator::Eq),
@@ -207,6 +702,7 @@ impl Display for Operator {
             Operator::Div => write!(f, "/"),
             Operator::Exp => write!(f, "**"),
             Operator::Floor => write!(f, "//"),
+            Operator::Mod => write!(f, "%"),
             Operator::Eq => write!(f, "="),
             Operator::AddEq => write!(f, "+="),
             Operator::SubEq => write!(f, "-="),
@@ -215,9 +711,9 @@ impl Display for Operator {
             Operator::ExpEq => write!(f, "**="),
             Operator::FloorEq => write!(f, "//="),
             Operator::BitAnd => write!(f, "&"),
-            Operator::LogAnd => write!(f, "and"),
+            Operator::LogAnd => write!(f, "&&"),
             Operator::BitOr => write!(f, "|"),
-            Operator::LogOr => write!(f, "or"),
+            Operator::LogOr => write!(f, "||"),
             Operator::Lt => write!(f, "<"),
             Operator::LtEq => write!(f, "<="),
             Operator::Mt => write!(f, ">"),
@@ -225,6 +721,500 @@ impl Display for Operator {
             Operator::Bang => write!(f, "!"),
             Operator::BangEq => write!(f, "!="),
             Operator::EqEq => write!(f, "=="),
+            Operator::Pipe => write!(f, "|>"),
+        }
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// VISITORS
+// ----------------------------------------------------------------- \\
+
+/// Walks the tree by shared reference. Passes override the `visit_*` methods for the
+/// nodes they care about and call the matching `walk_*` method to keep descending.
+///
+/// The walkers match every variant without a wildcard, so a new node won't compile
+/// until it is walked here and in [`VisitorMut`].
+pub trait Visitor {
+    fn visit_block(&mut self, stmts: &[Stmt]) {
+        self.walk_block(stmts);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.walk_stmt(stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.walk_expr(expr);
+    }
+
+    fn visit_type(&mut self, ty: &TypeExpr) {
+        self.walk_type(ty);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        self.walk_pattern(pattern);
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        self.walk_match_arm(arm);
+    }
+
+    fn visit_class(&mut self, decl: &ClassDecl) {
+        self.walk_class(decl);
+    }
+
+    fn visit_enum(&mut self, decl: &EnumDecl) {
+        self.walk_enum(decl);
+    }
+
+    /// Imports hold nothing to walk into, but passes may still want to see them
+    fn visit_import(&mut self, _decl: &ImportDecl) {}
+
+    fn walk_block(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn walk_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Variable { pattern, typ, value, .. } => {
+                self.visit_pattern(pattern);
+                if let Some(typ) = typ {
+                    self.visit_type(typ);
+                }
+                self.visit_expr(value);
+            }
+            StmtKind::Function { params, ret, body, .. } => {
+                for param in params {
+                    self.visit_expr(param);
+                }
+                if let Some(ret) = ret {
+                    self.visit_type(ret);
+                }
+                self.visit_block(body);
+            }
+            StmtKind::Class(decl) => self.visit_class(decl),
+            StmtKind::Enum(decl) => self.visit_enum(decl),
+            StmtKind::Import(decl) => self.visit_import(decl),
+            StmtKind::Return { value } => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+            }
+            StmtKind::If { cond, then, otherwise } => {
+                self.visit_expr(cond);
+                self.visit_block(then);
+                if let Some(otherwise) = otherwise {
+                    self.visit_block(otherwise);
+                }
+            }
+            StmtKind::Match { value, arms } => {
+                self.visit_expr(value);
+                for arm in arms {
+                    self.visit_match_arm(arm);
+                }
+            }
+            StmtKind::For { binding, iterable, body } => {
+                self.visit_pattern(binding);
+                self.visit_expr(iterable);
+                self.visit_block(body);
+            }
+            StmtKind::Expression { expr } => self.visit_expr(expr),
+            StmtKind::FieldInit { value, .. } => self.visit_expr(value),
+            StmtKind::Error => {}
+        }
+    }
+
+    fn walk_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Integer { .. } |
+            ExprKind::Float { .. } |
+            ExprKind::String { .. } |
+            ExprKind::Boolean { .. } |
+            ExprKind::Ident { .. } |
+            ExprKind::Default |
+            ExprKind::Error => {}
+            ExprKind::ArrayLiteral { elements } |
+            ExprKind::SeqLiteral { elements } |
+            ExprKind::Tuple { elements } => {
+                for element in elements {
+                    self.visit_expr(element);
+                }
+            }
+            ExprKind::ArrayRepeat { value, count } => {
+                self.visit_expr(value);
+                self.visit_expr(count);
+            }
+            ExprKind::Call { callee, args } => {
+                self.visit_expr(callee);
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+            ExprKind::Field { object, .. } => self.visit_expr(object),
+            ExprKind::Index { object, index } => {
+                self.visit_expr(object);
+                self.visit_expr(index);
+            }
+            ExprKind::Range { start, end, .. } => {
+                if let Some(start) = start {
+                    self.visit_expr(start);
+                }
+                if let Some(end) = end {
+                    self.visit_expr(end);
+                }
+            }
+            ExprKind::Variant { args, .. } => {
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+            ExprKind::TypeApply { callee, args } => {
+                self.visit_expr(callee);
+                for arg in args {
+                    self.visit_type(arg);
+                }
+            }
+            ExprKind::Assignment { assignee, value, .. } => {
+                self.visit_expr(assignee);
+                self.visit_expr(value);
+            }
+            ExprKind::Parameter { ty, .. } => {
+                if let Some(ty) = ty {
+                    self.visit_type(ty);
+                }
+            }
+            ExprKind::Lambda { params, ret, body } => {
+                for param in params {
+                    self.visit_expr(param);
+                }
+                if let Some(ret) = ret {
+                    self.visit_type(ret);
+                }
+                self.visit_block(body);
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.visit_expr(lhs);
+                self.visit_expr(rhs);
+            }
+            ExprKind::Is { value, ty, .. } => {
+                self.visit_expr(value);
+                self.visit_type(ty);
+            }
+        }
+    }
+
+    fn walk_type(&mut self, ty: &TypeExpr) {
+        match &ty.kind {
+            TypeKind::Named { .. } | TypeKind::Error => {}
+            TypeKind::Generic { args, .. } => {
+                for arg in args {
+                    self.visit_type(arg);
+                }
+            }
+            TypeKind::Nullable { inner } => self.visit_type(inner),
+            TypeKind::Array { elem, size } => {
+                self.visit_type(elem);
+                self.visit_expr(size);
+            }
+            TypeKind::Sequence { elem } => self.visit_type(elem),
+            TypeKind::Tuple { elems } => {
+                for elem in elems {
+                    self.visit_type(elem);
+                }
+            }
+            TypeKind::Function { params, ret } => {
+                for param in params {
+                    self.visit_type(param);
+                }
+                if let Some(ret) = ret {
+                    self.visit_type(ret);
+                }
+            }
+        }
+    }
+
+    fn walk_pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::None | PatternKind::Binding { .. } | PatternKind::Error => {}
+            PatternKind::Literal { value } => self.visit_expr(value),
+            PatternKind::Tuple { elements: fields } | PatternKind::Variant { fields, .. } => {
+                for field in fields {
+                    self.visit_pattern(field);
+                }
+            }
+        }
+    }
+
+    fn walk_match_arm(&mut self, arm: &MatchArm) {
+        self.visit_pattern(&arm.pattern);
+        if let Some(guard) = &arm.guard {
+            self.visit_expr(guard);
+        }
+        self.visit_block(&arm.body);
+    }
+
+    fn walk_class(&mut self, decl: &ClassDecl) {
+        for field in &decl.fields {
+            self.visit_type(&field.ty);
+        }
+        if let Some(constructor) = &decl.constructor {
+            self.visit_stmt(constructor);
+        }
+        for method in &decl.methods {
+            self.visit_stmt(method);
+        }
+    }
+
+    fn walk_enum(&mut self, decl: &EnumDecl) {
+        for variant in &decl.variants {
+            for ty in &variant.payload {
+                self.visit_type(ty);
+            }
+        }
+    }
+}
+
+/// Same as [`Visitor`] but by mutable reference, for passes that rewrite the tree in place
+pub trait VisitorMut {
+    fn visit_block(&mut self, stmts: &mut Vec<Stmt>) {
+        self.walk_block(stmts);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        self.walk_stmt(stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        self.walk_expr(expr);
+    }
+
+    fn visit_type(&mut self, ty: &mut TypeExpr) {
+        self.walk_type(ty);
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        self.walk_pattern(pattern);
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        self.walk_match_arm(arm);
+    }
+
+    fn visit_class(&mut self, decl: &mut ClassDecl) {
+        self.walk_class(decl);
+    }
+
+    fn visit_enum(&mut self, decl: &mut EnumDecl) {
+        self.walk_enum(decl);
+    }
+
+    fn visit_import(&mut self, _decl: &mut ImportDecl) {}
+
+    fn walk_block(&mut self, stmts: &mut Vec<Stmt>) {
+        for stmt in stmts {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn walk_stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Variable { pattern, typ, value, .. } => {
+                self.visit_pattern(pattern);
+                if let Some(typ) = typ {
+                    self.visit_type(typ);
+                }
+                self.visit_expr(value);
+            }
+            StmtKind::Function { params, ret, body, .. } => {
+                for param in params {
+                    self.visit_expr(param);
+                }
+                if let Some(ret) = ret {
+                    self.visit_type(ret);
+                }
+                self.visit_block(body);
+            }
+            StmtKind::Class(decl) => self.visit_class(decl),
+            StmtKind::Enum(decl) => self.visit_enum(decl),
+            StmtKind::Import(decl) => self.visit_import(decl),
+            StmtKind::Return { value } => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+            }
+            StmtKind::If { cond, then, otherwise } => {
+                self.visit_expr(cond);
+                self.visit_block(then);
+                if let Some(otherwise) = otherwise {
+                    self.visit_block(otherwise);
+                }
+            }
+            StmtKind::Match { value, arms } => {
+                self.visit_expr(value);
+                for arm in arms {
+                    self.visit_match_arm(arm);
+                }
+            }
+            StmtKind::For { binding, iterable, body } => {
+                self.visit_pattern(binding);
+                self.visit_expr(iterable);
+                self.visit_block(body);
+            }
+            StmtKind::Expression { expr } => self.visit_expr(expr),
+            StmtKind::FieldInit { value, .. } => self.visit_expr(value),
+            StmtKind::Error => {}
+        }
+    }
+
+    fn walk_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Integer { .. } |
+            ExprKind::Float { .. } |
+            ExprKind::String { .. } |
+            ExprKind::Boolean { .. } |
+            ExprKind::Ident { .. } |
+            ExprKind::Default |
+            ExprKind::Error => {}
+            ExprKind::ArrayLiteral { elements } |
+            ExprKind::SeqLiteral { elements } |
+            ExprKind::Tuple { elements } => {
+                for element in elements {
+                    self.visit_expr(element);
+                }
+            }
+            ExprKind::ArrayRepeat { value, count } => {
+                self.visit_expr(value);
+                self.visit_expr(count);
+            }
+            ExprKind::Call { callee, args } => {
+                self.visit_expr(callee);
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+            ExprKind::Field { object, .. } => self.visit_expr(object),
+            ExprKind::Index { object, index } => {
+                self.visit_expr(object);
+                self.visit_expr(index);
+            }
+            ExprKind::Range { start, end, .. } => {
+                if let Some(start) = start {
+                    self.visit_expr(start);
+                }
+                if let Some(end) = end {
+                    self.visit_expr(end);
+                }
+            }
+            ExprKind::Variant { args, .. } => {
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+            ExprKind::TypeApply { callee, args } => {
+                self.visit_expr(callee);
+                for arg in args {
+                    self.visit_type(arg);
+                }
+            }
+            ExprKind::Assignment { assignee, value, .. } => {
+                self.visit_expr(assignee);
+                self.visit_expr(value);
+            }
+            ExprKind::Parameter { ty, .. } => {
+                if let Some(ty) = ty {
+                    self.visit_type(ty);
+                }
+            }
+            ExprKind::Lambda { params, ret, body } => {
+                for param in params {
+                    self.visit_expr(param);
+                }
+                if let Some(ret) = ret {
+                    self.visit_type(ret);
+                }
+                self.visit_block(body);
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.visit_expr(lhs);
+                self.visit_expr(rhs);
+            }
+            ExprKind::Is { value, ty, .. } => {
+                self.visit_expr(value);
+                self.visit_type(ty);
+            }
+        }
+    }
+
+    fn walk_type(&mut self, ty: &mut TypeExpr) {
+        match &mut ty.kind {
+            TypeKind::Named { .. } | TypeKind::Error => {}
+            TypeKind::Generic { args, .. } => {
+                for arg in args {
+                    self.visit_type(arg);
+                }
+            }
+            TypeKind::Nullable { inner } => self.visit_type(inner),
+            TypeKind::Array { elem, size } => {
+                self.visit_type(elem);
+                self.visit_expr(size);
+            }
+            TypeKind::Sequence { elem } => self.visit_type(elem),
+            TypeKind::Tuple { elems } => {
+                for elem in elems {
+                    self.visit_type(elem);
+                }
+            }
+            TypeKind::Function { params, ret } => {
+                for param in params {
+                    self.visit_type(param);
+                }
+                if let Some(ret) = ret {
+                    self.visit_type(ret);
+                }
+            }
+        }
+    }
+
+    fn walk_pattern(&mut self, pattern: &mut Pattern) {
+        match &mut pattern.kind {
+            PatternKind::Wildcard | PatternKind::None | PatternKind::Binding { .. } | PatternKind::Error => {}
+            PatternKind::Literal { value } => self.visit_expr(value),
+            PatternKind::Tuple { elements: fields } | PatternKind::Variant { fields, .. } => {
+                for field in fields {
+                    self.visit_pattern(field);
+                }
+            }
+        }
+    }
+
+    fn walk_match_arm(&mut self, arm: &mut MatchArm) {
+        self.visit_pattern(&mut arm.pattern);
+        if let Some(guard) = &mut arm.guard {
+            self.visit_expr(guard);
+        }
+        self.visit_block(&mut arm.body);
+    }
+
+    fn walk_class(&mut self, decl: &mut ClassDecl) {
+        for field in &mut decl.fields {
+            self.visit_type(&mut field.ty);
+        }
+        if let Some(constructor) = &mut decl.constructor {
+            self.visit_stmt(constructor);
+        }
+        for method in &mut decl.methods {
+            self.visit_stmt(method);
+        }
+    }
+
+    fn walk_enum(&mut self, decl: &mut EnumDecl) {
+        for variant in &mut decl.variants {
+            for ty in &mut variant.payload {
+                self.visit_type(ty);
+            }
         }
     }
 }
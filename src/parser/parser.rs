@@ -1,16 +1,61 @@
+use std::cell::Cell;
 use crate::{
-    common::errors::{ Error, ErrorBuffer, ErrorKind, ErrorWriter },
+    common::{ errors::{ Error, ErrorBuffer, ErrorKind, ErrorWriter }, span::Span, trace::Tracer },
     expr,
     lexer::token::{ Tk, Token },
     stmt,
+    syntax::{ builder::SyntaxBuilder, green::GreenNode, typed::AstNode },
     throw,
 };
-use super::{ ast::{ Expr, ExprKind, Operator, Stmt, StmtKind } };
+use super::ast::{
+    ClassDecl,
+    Constraint,
+    EnumDecl,
+    Expr,
+    ExprKind,
+    FieldDecl,
+    ImportDecl,
+    ImportItem,
+    Imported,
+    MatchArm,
+    Modifier,
+    NodeId,
+    Operator,
+    Pattern,
+    PatternKind,
+    Stmt,
+    StmtKind,
+    TypeExpr,
+    TypeKind,
+    TypeParam,
+    VariantDecl,
+};
 
 // ----------------------------------------------------------------- \\
 // PARSER IMPLEMENTATION
 // ----------------------------------------------------------------- \\
 
+/// How deeply expressions, types, patterns and statements may nest inside of each
+/// other. Chains count as well, `a + b + c` is folded into `(a + b) + c` and every
+/// pass after the parser recurses through that just like through parentheses.
+/// This fits comfortably in the 8MB stack of a main thread, even in debug builds.
+pub const MAX_NESTING: usize = 200;
+
+/// Everything in a function declaration that comes before its body
+struct FunctionHeader {
+    name: String,
+    type_params: Vec<TypeParam>,
+    params: Vec<Expr>,
+    ret: Option<TypeExpr>,
+    where_clause: Vec<Constraint>,
+}
+
+/// What a pair of parentheses turned out to hold
+enum Parenthesized<T> {
+    Group(T),
+    Tuple(Vec<T>),
+}
+
 pub struct Parser {
     /// The token input stream of the parser, flat and should never be mutated.
     tokens: Vec<Token>,
@@ -21,8 +66,25 @@ pub struct Parser {
     /// The position of the parser in the token stream.
     pos: usize,
     
-    /// The UID of the next node in the syntax tree.
-    uid: usize,
+    /// The UID of the next node in the syntax tree, in a cell so that handing one
+    /// out doesn't need the whole parser.
+    uid: Cell<usize>,
+
+    /// How many nested expressions, types, patterns and statements enclose the
+    /// current position, capped at `MAX_NESTING`.
+    depth: usize,
+
+    /// The deepest level the tree built so far reaches, see `Parser::measured`
+    reached: usize,
+
+    /// Where the rules the parser enters and leaves are written, if tracing is on
+    tracer: Option<Tracer>,
+
+    /// The lossless syntax tree, if one is built alongside the AST
+    syntax: Option<SyntaxBuilder>,
+
+    /// How many of the tokens have been added to the syntax tree
+    in_syntax: usize,
 }
 
 // Error writer implementation to support recording errors in the centralized error buffer
@@ -37,19 +99,47 @@ impl ErrorWriter for Parser {
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Parser {
+    pub fn new(mut tokens: Vec<Token>) -> Parser {
+        // every helper relies on the stream being capped with an EOF token
+        if tokens.last().is_none_or(|tk| tk.kind != Tk::EOF) {
+            let end = tokens.last().map_or(0, |tk| tk.span.end);
+            tokens.push(Token::eof(end..end));
+        }
+
         return Parser {
             tokens,
             errors: vec![],
             pos: 0,
-            uid: 0,
+            uid: Cell::new(0),
+            depth: 0,
+            reached: 0,
+            tracer: None,
+            syntax: None,
+            in_syntax: 0,
         };
     }
 
+    /// Writes every rule the parser enters and leaves to `tracer`
+    pub fn trace(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    /// Builds a lossless syntax tree while parsing, out of the whole token stream
+    /// of `source` that the parser's tokens were taken from, see [`Parser::syntax`]
+    pub fn keep_syntax(&mut self, source: &str, tokens: Vec<Token>) {
+        self.syntax = Some(SyntaxBuilder::new(source, tokens));
+    }
+
     pub fn parse(&mut self) -> (Vec<Stmt>, ErrorBuffer) {
+        self.add_syntax_tokens();
         let (ast, errors) = self.parse_program();
         return (ast, errors);
     }
+
+    /// Hands over the syntax tree built by the last parse, if it was asked for
+    pub fn syntax(&mut self) -> Option<GreenNode> {
+        return self.syntax.take().map(SyntaxBuilder::finish);
+    }
 }
 
 // ----------------------------------------------------------------- \\
@@ -78,9 +168,39 @@ impl Parser {
         return self.tokens.get(self.pos + 1).unwrap_or(&self.tokens[self.tokens.len() - 1]);
     }
 
+    /// Returns the first token past the current position that isn't a newline.
+    fn peek_past_newlines(&self) -> &Token {
+        let mut pos = self.pos + 1;
+        while self.tokens.get(pos).is_some_and(|tk| tk.kind == Tk::Newline) {
+            pos += 1;
+        }
+        return self.tokens.get(pos).unwrap_or(&self.tokens[self.tokens.len() - 1]);
+    }
+
     /// Moves the position of the parser forward but will never exceed the EOF index.
     fn consume(&mut self) {
         self.pos = (self.pos + 1).clamp(0, self.tokens.len() - 1);
+        self.add_syntax_tokens();
+    }
+
+    /// Adds the tokens up to the current one to the syntax tree, if one is built.
+    /// A rule starts and ends on a current token, so it is in the tree by then.
+    fn add_syntax_tokens(&mut self) {
+        if let Some(syntax) = &mut self.syntax {
+            while self.in_syntax <= self.pos {
+                syntax.token(&self.tokens[self.in_syntax]);
+                self.in_syntax += 1;
+            }
+        }
+    }
+
+    /// Adds a node the parser just finished to the syntax tree, if one is built,
+    /// around the tokens and nodes it was parsed from
+    fn node<N: AstNode>(&mut self, node: N) -> N {
+        if let Some(syntax) = &mut self.syntax {
+            syntax.node(node.syntax_kind(), node.uid(), node.span());
+        }
+        return node;
     }
 
     /// Expects the next token to be of the prescribed type and consume if it is. If it isn't, it will not consume but it will return an error.
@@ -144,29 +264,60 @@ impl Parser {
         }
     }
 
-    /// Starts from the current thing and then looks for something to end the
-    /// current line and start a new statment.
-    ///
-    /// This function will end on the semicolon/newline, NOT on the first
-    /// thing of the next line, so call `self.advance()`.
+    /// Whether or not the token can legally follow a complete statement.
+    fn ends_stmt(kind: Tk) -> bool {
+        return matches!(kind, Tk::Semicolon | Tk::Newline | Tk::End | Tk::Else | Tk::Case | Tk::EOF);
+    }
+
+    /// Keeps advancing until the current token is NOT a newline or semicolon
+    fn skip_terminators(&mut self) {
+        while matches!(self.current().kind, Tk::Newline | Tk::Semicolon) {
+            self.consume();
+        }
+    }
+
+    /// Skips tokens until the parser reaches a point where a new statement can
+    /// begin: a semicolon/newline, the `end` of the enclosing block, EOF or a keyword
+    /// that can only start a statement. Stops right away if it's already there.
     fn sync(&mut self) {
         while !self.at_end() {
-            match self.current().kind {
-                Tk::EOF | Tk::Semicolon | Tk::Newline => {
-                    break;
-                }
-                _ => {}
+            let kind = self.current().kind;
+            if Self::ends_stmt(kind) || kind.starts_stmt() {
+                break;
             }
             self.consume();
         }
     }
 
-    /// Similar to `sync` but will look for the END token instead
-    fn sync_after_fn(&mut self) {
-        while !self.at_end() {
-            match self.current().kind {
+    /// Skips the remainder of a broken item in a delimited list. Stops so that the
+    /// next token is a `,` or the closing delimiter at the same nesting depth, or
+    /// something that clearly doesn't belong to the list anymore.
+    fn sync_list(&mut self, close: Tk) {
+        let mut depth = 0usize;
+        loop {
+            let next = self.peek().kind;
+            match next {
                 Tk::EOF | Tk::End => {
-                    break;
+                    return;
+                }
+                Tk::Comma if depth == 0 => {
+                    return;
+                }
+                _ if next == close && depth == 0 => {
+                    return;
+                }
+                _ if next.starts_stmt() && depth == 0 => {
+                    return;
+                }
+                Tk::LParen | Tk::LBrac | Tk::LCurl => {
+                    depth += 1;
+                }
+                Tk::RParen | Tk::RBrac | Tk::RCurl => {
+                    // a stray closer of a different kind means we've left the list
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
                 }
                 _ => {}
             }
@@ -174,17 +325,78 @@ impl Parser {
         }
     }
 
-    fn emit_diagnostics(&self, location: &str) {
-        println!("\nLOCATED: {}", location);
-        println!("CURRENT: {:?}", self.current().kind);
-        println!("NEXT: {:?}", self.peek().kind);
+    /// Creates a span from the start of the token at `from` up to the end of the
+    /// last token the parser moved past.
+    fn span_from(&self, from: usize) -> Span {
+        let first = &self.tokens[from.min(self.tokens.len() - 1)];
+        let last = &self.tokens[self.pos.saturating_sub(1).max(from).min(self.tokens.len() - 1)];
+        return first.span.start..last.span.end.max(first.span.start);
     }
 
     /// Provides a unique ID for the next node
     /// and advances the internal UID counter.
-    fn id(&mut self) -> usize {
-        self.uid += 1;
-        return self.uid - 1;
+    fn id(&self) -> NodeId {
+        let uid = self.uid.get();
+        self.uid.set(uid + 1);
+        return NodeId(uid);
+    }
+
+    /// Runs the parser for `rule` one level of nesting deeper, refusing to go past
+    /// `MAX_NESTING` so that deeply nested code is reported instead of overflowing
+    /// the stack. This is also where rules are traced, when tracing is on.
+    fn nested<T>(
+        &mut self,
+        rule: &str,
+        parser: impl FnOnce(&mut Parser) -> Result<T, Error>
+    ) -> Result<T, Error> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.too_deep());
+        }
+
+        if let Some(tracer) = &self.tracer {
+            tracer.enter(rule, self.depth, self.current());
+        }
+        self.depth += 1;
+        self.reached = self.reached.max(self.depth);
+        let result = parser(self);
+        self.depth -= 1;
+        if let Some(tracer) = &self.tracer {
+            tracer.exit(rule, self.depth, self.current(), result.is_ok());
+        }
+        return result;
+    }
+
+    /// Runs `parser` and measures how many levels below the current depth the tree
+    /// it builds reaches. Chains are folded after their parts are parsed, so this is
+    /// how they find out how deep they end up.
+    fn measured<T>(
+        &mut self,
+        parser: impl FnOnce(&mut Parser) -> Result<T, Error>
+    ) -> Result<(T, usize), Error> {
+        let outer = self.reached;
+        self.reached = self.depth;
+        let result = parser(self);
+        let height = self.reached - self.depth;
+        self.reached = self.reached.max(outer);
+        return result.map(|item| (item, height));
+    }
+
+    /// Records that the node being built reaches `height` levels below the current
+    /// depth, which counts against `MAX_NESTING` like any other nesting
+    fn reach(&mut self, height: usize) -> Result<(), Error> {
+        if self.depth + height > MAX_NESTING {
+            return Err(self.too_deep());
+        }
+        self.reached = self.reached.max(self.depth + height);
+        return Ok(());
+    }
+
+    fn too_deep(&self) -> Error {
+        return throw!(
+            SyntaxError,
+            self.current().span.clone(),
+            format!("this is nested too deeply, the limit is {MAX_NESTING} levels")
+        );
     }
 }
 
@@ -201,7 +413,7 @@ impl Parser {
                 return Err(throw!(ParseError, tk.span, "error parsing integer literal"));
             }
         };
-        return Ok(Expr::new(self.id(), ExprKind::Integer { value }, tk.span));
+        return Ok(self.node(Expr::new(self.id(), ExprKind::Integer { value }, tk.span)));
     }
 
     fn parse_float(&mut self) -> Result<Expr, Error> {
@@ -212,109 +424,283 @@ impl Parser {
                 return Err(throw!(ParseError, tk.span.clone(), "error parsing float literal"));
             }
         };
-        return Ok(Expr::new(self.id(), ExprKind::Float { value }, tk.span));
+        return Ok(self.node(Expr::new(self.id(), ExprKind::Float { value }, tk.span)));
     }
 
     fn parse_ident(&mut self) -> Result<Expr, Error> {
         let tk = self.current_owned();
         let name = tk.lexeme;
-        return Ok(Expr::new(self.id(), ExprKind::Ident { name }, tk.span));
+        return Ok(self.node(Expr::new(self.id(), ExprKind::Ident { name }, tk.span)));
     }
 
     fn parse_string(&mut self) -> Result<Expr, Error> {
         let tk = self.current_owned();
         let value = tk.lexeme;
-        return Ok(Expr::new(self.id(), ExprKind::String { value }, tk.span));
+        return Ok(self.node(Expr::new(self.id(), ExprKind::String { value }, tk.span)));
+    }
+
+    /// Parses either an array literal `[a, b, c]` or a repeated array `[value; count]`,
+    /// starting on '[' and ending on ']'
+    fn parse_array(&mut self) -> Result<Expr, Error> {
+        let start = self.current().span.start;
+
+        self.skip_next_newlines();
+        if self.expect_next(Tk::RBrac) {
+            let span = start..self.current().span.end;
+            return Ok(self.node(expr!(ArrayLiteral, self.id(), vec![], span)));
+        }
+
+        self.consume(); // go to start of first element
+        let first = self.expr()?;
+
+        // [value; count]
+        if self.expect_next_ignore_newln(Tk::Semicolon) {
+            self.skip_next_newlines();
+            self.consume(); // go to start of count
+            let count = self.expr()?;
+            self.assert_next_ignore_newln(Tk::RBrac, "expected ']' to close repeated array")?;
+
+            let span = start..self.current().span.end;
+            return Ok(self.node(expr!(ArrayRepeat, self.id(), first, count, span)));
+        }
+
+        // [a, b, c]
+        let mut elements = vec![first];
+        if self.expect_next_ignore_newln(Tk::Comma) {
+            elements.extend(
+                self.parse_list(
+                    Tk::RBrac,
+                    "array elements",
+                    |p| p.expr(),
+                    |p, span| p.node(Expr::new(p.id(), ExprKind::Error, span))
+                )
+            );
+        } else {
+            self.assert_next_ignore_newln(Tk::RBrac, "expected ',' or ']' after array element")?;
+        }
+
+        let span = start..self.current().span.end;
+        return Ok(self.node(expr!(ArrayLiteral, self.id(), elements, span)));
+    }
+
+    /// Parses a sequence literal `{ a, b, c }`, starting on '{' and ending on '}'
+    fn parse_sequence(&mut self) -> Result<Expr, Error> {
+        let start = self.current().span.start;
+        let elements = self.parse_list(
+            Tk::RCurl,
+            "sequence elements",
+            |p| p.expr(),
+            |p, span| p.node(Expr::new(p.id(), ExprKind::Error, span))
+        );
+
+        let span = start..self.current().span.end;
+        return Ok(self.node(expr!(SeqLiteral, self.id(), elements, span)));
     }
 
-    fn parse_args(&mut self) -> Result<Vec<Expr>, Error> {
-        let mut args = Vec::<Expr>::new();
+    /// Parses an anonymous function, either spread over several lines or written
+    /// on one like `function(x: int) return x + 1; end`. Starts on FUNCTION and
+    /// ends on END.
+    fn parse_lambda(&mut self) -> Result<Expr, Error> {
+        let start = self.current().span.start;
+        self.assert_next(Tk::LParen, "expected '(' to begin lambda parameters".to_string())?;
+        let params = self.parse_params();
+
+        let mut ret: Option<TypeExpr> = None;
+        if self.expect_next(Tk::RArrow) {
+            self.consume(); // go to start of return type
+            ret = Some(self.type_expr()?);
+        }
+
         self.consume();
+        let body = self.parse_block();
 
-        // start: first expr of arg 1
-        loop {
-            if self.current().kind == Tk::RParen {
-                break;
-            }
+        let span = start..self.current().span.end;
+        return Ok(self.node(expr!(Lambda, self.id(), params, ret, body, span)));
+    }
 
-            let expr = self.expr()?;
-            args.push(expr);
+    /// Parses a tuple, or an expression in parentheses, starting on '(' and ending on ')'
+    fn parse_tuple(&mut self) -> Result<Expr, Error> {
+        let start = self.current().span.start;
+        let parenthesized = self.parse_parenthesized(
+            "tuple elements",
+            |p| p.expr(),
+            |p, span| p.node(Expr::new(p.id(), ExprKind::Error, span))
+        )?;
 
-            // next is either COMMA or RPAREN
-            if self.expect_next_ignore_newln(Tk::Comma) {
-                self.consume();
-                continue;
-            } else {
-                self.assert_next_ignore_newln(
-                    Tk::RParen,
-                    "expected ')' to close function call arguments"
-                )?;
-                break;
+        let span = start..self.current().span.end;
+        return Ok(match parenthesized {
+            Parenthesized::Group(mut expr) => {
+                expr.span = span;
+                self.node(expr)
             }
+            Parenthesized::Tuple(elements) => self.node(expr!(Tuple, self.id(), elements, span)),
+        });
+    }
+
+    /// Parses whatever is in a pair of parentheses, starting on '(' and ending on ')'.
+    /// A single item without a trailing comma is only grouped, anything else is a tuple.
+    fn parse_parenthesized<T>(
+        &mut self,
+        what: &str,
+        mut item: impl FnMut(&mut Parser) -> Result<T, Error>,
+        recover: impl FnMut(&mut Parser, Span) -> T
+    ) -> Result<Parenthesized<T>, Error> {
+        self.skip_next_newlines();
+        if self.expect_next(Tk::RParen) {
+            return Ok(Parenthesized::Tuple(vec![]));
         }
 
-        // end: RPAREN
-        return Ok(args);
+        self.consume(); // go to start of first item
+        let first = item(self)?;
+
+        if self.expect_next_ignore_newln(Tk::Comma) {
+            let mut items = vec![first];
+            items.extend(self.parse_list(Tk::RParen, what, item, recover));
+            return Ok(Parenthesized::Tuple(items));
+        }
+
+        self.assert_next_ignore_newln(Tk::RParen, "expected ',' or ')' in parentheses")?;
+        return Ok(Parenthesized::Group(first));
     }
 
-    fn parse_params(&mut self) -> Result<Vec<Expr>, Error> {
-        let mut params = Vec::<Expr>::new();
+    /// Parses a comma separated list of items, starting on the opening delimiter and
+    /// ending on `close`. Trailing commas and newlines between items are allowed.
+    ///
+    /// Items that fail to parse are reported and replaced with whatever `recover`
+    /// builds from the span of the skipped tokens, so one bad item doesn't
+    /// take the rest of the list down with it.
+    fn parse_list<T>(
+        &mut self,
+        close: Tk,
+        what: &str,
+        mut item: impl FnMut(&mut Parser) -> Result<T, Error>,
+        mut recover: impl FnMut(&mut Parser, Span) -> T
+    ) -> Vec<T> {
+        let mut items = Vec::<T>::new();
+        let closer = match close {
+            Tk::RParen => ")",
+            Tk::RBrac => "]",
+            Tk::RCurl => "}",
+            Tk::More => ">",
+            Tk::End => "end",
+            _ => "end of list",
+        };
 
-        // start: first expr of param 1
         loop {
-            if self.current().kind == Tk::RParen {
+            self.skip_next_newlines();
+            if self.expect_next(close) {
                 break;
             }
 
-            self.assert_current(Tk::Ident, "expected parameter name".to_string())?;
-            let name = self.current().lexeme.clone();
-            let start = self.current().span.start;
-
-            self.assert_next(
-                Tk::Colon,
-                format!("expected colon after parameter name, got '{}'", self.current().lexeme)
-            )?;
-            self.consume(); // go to start of type expression
+            match self.peek().kind {
+                Tk::EOF | Tk::End => {
+                    let span = self.peek().span.clone();
+                    self.error(throw!(SyntaxError, span, format!("expected '{closer}' to close {what}")));
+                    break;
+                }
+                Tk::Comma => {
+                    self.consume();
+                    let span = self.current().span.clone();
+                    self.error(throw!(SyntaxError, span, format!("expected one of the {what}, got ','")));
+                    continue;
+                }
+                _ => {}
+            }
 
-            let typ = self.expr()?;
-            let span = start..typ.span.end;
-            params.push(expr!(Parameter, self.id(), name, typ, span));
+            // start: first token of the item
+            self.consume();
+            let from = self.pos;
+            match item(self) {
+                Ok(value) => items.push(value),
+                Err(err) => {
+                    // the token that broke the item was never part of it, so step back
+                    // and let the sync decide whether it ends the item or is junk
+                    self.error(err);
+                    if self.pos > from {
+                        self.pos -= 1;
+                    }
+                    self.sync_list(close);
+                    let span = self.tokens[from].span.start..self.current().span.end;
+                    items.push(recover(self, span));
+                }
+            }
 
-            // next is either COMMA or RPAREN
-            if self.expect_next_ignore_newln(Tk::Comma) {
-                self.consume();
+            // next is either COMMA or the closing delimiter
+            self.skip_next_newlines();
+            if self.expect_next(Tk::Comma) {
                 continue;
-            } else {
-                self.assert_next_ignore_newln(
-                    Tk::RParen,
-                    "expected ')' to close function parameters"
-                )?;
+            }
+            if self.expect_next(close) {
+                break;
+            }
+
+            let span = self.peek().span.clone();
+            self.error(throw!(SyntaxError, span, format!("expected ',' or '{closer}' after {what}")));
+            self.sync_list(close);
+            if self.expect_next(close) || !self.expect_next(Tk::Comma) {
                 break;
             }
         }
 
-        // end: RPAREN
-        return Ok(params);
+        // end: closing delimiter (or the last thing before the list was abandoned)
+        return items;
+    }
+
+    fn parse_args(&mut self) -> Vec<Expr> {
+        return self.parse_list(
+            Tk::RParen,
+            "function call arguments",
+            |p| p.expr(),
+            |p, span| p.node(Expr::new(p.id(), ExprKind::Error, span))
+        );
+    }
+
+    fn parse_param(&mut self) -> Result<Expr, Error> {
+        self.assert_current(Tk::Ident, "expected parameter name".to_string())?;
+        let name = self.current().lexeme.clone();
+        let mut span = self.current().span.clone();
+
+        // the type can be left off, like for `self` in methods
+        let mut typ: Option<TypeExpr> = None;
+        if self.expect_next(Tk::Colon) {
+            self.consume(); // go to start of type expression
+            let ty = self.type_expr()?;
+            span.end = ty.span.end;
+            typ = Some(ty);
+        }
+
+        return Ok(self.node(expr!(Parameter, self.id(), name, typ, span)));
+    }
+
+    fn parse_params(&mut self) -> Vec<Expr> {
+        return self.parse_list(
+            Tk::RParen,
+            "function parameters",
+            |p| p.parse_param(),
+            |p, span| p.node(Expr::new(p.id(), ExprKind::Error, span))
+        );
     }
 
+    /// Parses statements until the `end` that closes the block, starting anywhere
+    /// before the first statement and ending on the END token.
     fn parse_block(&mut self) -> Vec<Stmt> {
+        return self.parse_block_until(&[Tk::End]);
+    }
+
+    /// Same as `parse_block` but the block can also be closed by any of the given
+    /// tokens, like ELSE for the branches of an if statement
+    fn parse_block_until(&mut self, closers: &[Tk]) -> Vec<Stmt> {
         let mut stmts: Vec<Stmt> = vec![];
 
-        while !self.at_end() {
-            self.skip_newlines();
-            if self.expect_current(Tk::End) {
+        loop {
+            self.skip_terminators();
+            if closers.contains(&self.current().kind) {
                 return stmts;
             }
-
-            match self.stmt() {
-                Ok(stmt) => stmts.push(stmt),
-                Err(err) => {
-                    self.error(err);
-                    self.sync();
-                }
+            if self.at_end() {
+                break;
             }
-
-            self.consume();
+            stmts.push(self.stmt_or_error("stmt", Parser::stmt));
         }
 
         // this only happens if there's no END to close
@@ -324,6 +710,55 @@ impl Parser {
         );
         return stmts;
     }
+
+    /// Runs a statement parser and makes sure the statement is properly terminated.
+    /// If the statement fails to parse an error node covering the skipped tokens
+    /// takes the place of the statement.
+    fn stmt_or_error(&mut self, rule: &str, parser: fn(&mut Parser) -> Result<Stmt, Error>) -> Stmt {
+        let from = self.pos;
+        return match self.terminated(rule, parser) {
+            Some(stmt) => stmt,
+            None => self.node(Stmt::new(self.id(), StmtKind::Error, self.span_from(from))),
+        };
+    }
+
+    /// Runs the parser for anything that is terminated like a statement and checks
+    /// that it is followed by a semicolon, new line, END or EOF.
+    ///
+    /// If it fails to parse the error is recorded, the parser resynchronizes and
+    /// `None` is returned. Either way this ends on the token after the statement,
+    /// which is never skipped if it could start the next one.
+    fn terminated<T>(&mut self, rule: &str, parser: impl FnOnce(&mut Parser) -> Result<T, Error>) -> Option<T> {
+        let from = self.pos;
+
+        match self.nested(rule, parser) {
+            Ok(item) => {
+                // look for end of stmt
+                let next = self.peek().kind;
+                self.consume();
+                if !Self::ends_stmt(next) {
+                    self.error(
+                        throw!(
+                            SyntaxError,
+                            self.current().span.clone(),
+                            "expected ';' or new line to complete statement"
+                        )
+                    );
+                    self.sync();
+                }
+                return Some(item);
+            }
+            Err(err) => {
+                // always skip the offending token so that recovery makes progress
+                self.error(err);
+                if self.pos == from {
+                    self.consume();
+                }
+                self.sync();
+                return None;
+            }
+        }
+    }
 }
 
 // ----------------------------------------------------------------- \\
@@ -337,11 +772,19 @@ impl Parser {
             Tk::Float => self.parse_float(),
             Tk::Ident => self.parse_ident(),
             Tk::String => self.parse_string(),
+            Tk::LBrac => self.parse_array(),
+            Tk::LCurl => self.parse_sequence(),
+            Tk::LParen => self.parse_tuple(),
+            Tk::Function => self.parse_lambda(),
+            Tk::Default => {
+                let span = self.current().span.clone();
+                return Ok(self.node(Expr::new(self.id(), ExprKind::Default, span)));
+            }
 
             Tk::True | Tk::False => {
                 let tk = self.current_owned();
                 let value = tk.kind == Tk::True;
-                return Ok(Expr::new(self.id(), ExprKind::Boolean { value }, tk.span));
+                return Ok(self.node(Expr::new(self.id(), ExprKind::Boolean { value }, tk.span)));
             }
 
             _ => {
@@ -357,44 +800,242 @@ impl Parser {
         }
     }
 
-    fn expr_call(&mut self) -> Result<Expr, Error> {
-        let mut expr = self.expr_literal()?;
+    /// Parses calls, field accesses, indexing and type arguments trailing a literal.
+    /// These can be chained to any depth, like `seq.sort().rev()[0]` or `int{}.map(f)`,
+    /// and each link spans from the start of the chain to its own closing token.
+    fn expr_postfix(&mut self) -> Result<Expr, Error> {
+        // every link wraps the chain so far, which ends up as deep as its longest part
+        let (mut expr, mut height) = self.measured(Parser::expr_literal)?;
 
-        if self.expect_next(Tk::LParen) {
-            self.skip_newlines();
-            let args = self.parse_args()?;
-            let span = expr.span.start..self.current().span.end;
-            expr = expr!(Call, self.id(), expr, args, span);
+        loop {
+            let start = expr.span.start;
+            match self.peek().kind {
+                Tk::LParen => {
+                    self.consume();
+                    let (args, args_height) = self.measured(|parser| Ok(parser.parse_args()))?;
+                    height = (height + 1).max(args_height);
+                    let span = start..self.current().span.end;
+                    expr = self.node(expr!(Call, self.id(), expr, args, span));
+                }
+                Tk::Dot => {
+                    self.consume();
+                    self.assert_next(
+                        Tk::Ident,
+                        format!("expected field name after '.', got '{}'", self.peek().lexeme)
+                    )?;
+                    let name = self.current().lexeme.clone();
+                    height += 1;
+                    let span = start..self.current().span.end;
+                    expr = self.node(expr!(Field, self.id(), expr, name, span));
+                }
+                Tk::LBrac => {
+                    self.consume();
+                    self.skip_next_newlines();
+                    self.consume(); // go to start of index expression
+                    let (index, index_height) = self.measured(Parser::expr_index)?;
+                    height = (height + 1).max(index_height);
+                    self.assert_next_ignore_newln(Tk::RBrac, "expected ']' to close index")?;
+                    let span = start..self.current().span.end;
+                    expr = self.node(expr!(Index, self.id(), expr, index, span));
+                }
+                Tk::LCurl => {
+                    self.consume();
+                    let (args, args_height) = self.measured(|parser| Ok(parser.parse_type_args(Tk::RCurl)))?;
+                    height = (height + 1).max(args_height);
+                    let span = start..self.current().span.end;
+                    expr = self.node(expr!(TypeApply, self.id(), expr, args, span));
+                }
+                _ => {
+                    break;
+                }
+            }
+            self.reach(height)?;
         }
 
         return Ok(expr);
     }
 
-    fn expr_binary(&mut self) -> Result<Expr, Error> {
-        let mut expr = self.expr_call()?;
+    /// Parses a chain of binary operators, only taking operators that bind at least
+    /// as tightly as `min_prec`. Chains are folded with a stack of pending operators
+    /// rather than recursion, each operand alongside how deep its tree goes, so a long
+    /// chain is reported as too deep instead of overflowing the stack.
+    fn expr_binary(&mut self, min_prec: u8) -> Result<Expr, Error> {
+        let mut operands = vec![self.measured(Parser::expr_operand)?];
+        let mut operators = Vec::<Operator>::new();
+
+        loop {
+            // a pipe may start the next line to continue the chain
+            if self.peek().kind == Tk::Newline && self.peek_past_newlines().kind == Tk::PipeMore {
+                self.skip_next_newlines();
+            }
+
+            // `is` binds like a comparison but its right side is a type
+            if self.peek().kind == Tk::Is {
+                let prec = Operator::Lt.precedence();
+                if prec < min_prec {
+                    break;
+                }
+                self.fold_operators(&mut operands, &mut operators, |op| op.precedence() >= prec)?;
+                let (value, value_height) = operands.pop().unwrap();
+                let (is, type_height) = self.measured(|parser| parser.expr_is(value))?;
+                let height = value_height.max(type_height) + 1;
+                self.reach(height)?;
+                operands.push((is, height));
+                continue;
+            }
+
+            let Some(op) = Operator::binary(&self.peek().kind) else {
+                break;
+            };
+            if op.precedence() < min_prec {
+                break;
+            }
+
+            // everything before that binds tighter is complete, as is the same
+            // operator on the left unless it groups from the right
+            self.fold_operators(&mut operands, &mut operators, |pending| {
+                pending.precedence() > op.precedence() ||
+                    (pending.precedence() == op.precedence() && !op.right_assoc())
+            })?;
 
-        if let Some(op) = Operator::binary(&self.peek().kind) {
             self.consume(); // consume the operator
+            self.skip_next_newlines();
             self.consume(); // go to start of next expr
-            self.skip_newlines();
-            let rhs = self.expr()?;
-            let span = expr.span.start..rhs.span.end;
-            expr = expr!(Binary, self.id(), expr, rhs, op, span);
+
+            operators.push(op);
+            operands.push(self.measured(Parser::expr_operand)?);
         }
 
-        return Ok(expr);
+        self.fold_operators(&mut operands, &mut operators, |_| true)?;
+        return Ok(operands.pop().unwrap().0);
+    }
+
+    /// Combines the last two operands with the last pending operator for as long as
+    /// `fold` accepts that operator, stopping as soon as the result gets too deep
+    fn fold_operators(
+        &mut self,
+        operands: &mut Vec<(Expr, usize)>,
+        operators: &mut Vec<Operator>,
+        fold: impl Fn(Operator) -> bool
+    ) -> Result<(), Error> {
+        while let Some(&op) = operators.last() && fold(op) {
+            operators.pop();
+            let (rhs, rhs_height) = operands.pop().unwrap();
+            let (lhs, lhs_height) = operands.pop().unwrap();
+            let height = lhs_height.max(rhs_height) + 1;
+            self.reach(height)?;
+            let span = lhs.span.start..rhs.span.end;
+
+            let expr = if op == Operator::Pipe {
+                self.desugar_pipe(lhs, rhs, span)
+            } else {
+                self.node(expr!(Binary, self.id(), lhs, rhs, op, span))
+            };
+            operands.push((expr, height));
+        }
+        return Ok(());
+    }
+
+    /// Parses a single operand of a binary chain. Anything nested inside of an
+    /// expression, from parentheses to call arguments and lambda bodies, passes
+    /// through here, which makes it the place to count nesting.
+    fn expr_operand(&mut self) -> Result<Expr, Error> {
+        return self.nested("operand", Parser::expr_postfix);
+    }
+
+    /// Parses the `is T` or `is not T` trailing `value`, ending on the last token of the type
+    fn expr_is(&mut self, value: Expr) -> Result<Expr, Error> {
+        self.consume(); // consume IS
+        let negated = self.expect_next(Tk::Not);
+
+        self.consume(); // go to start of type expression
+        let ty = self.type_expr()?;
+
+        let span = value.span.start..ty.span.end;
+        return Ok(self.node(expr!(Is, self.id(), value, ty, negated, span)));
+    }
+
+    /// Turns `value |> f(args)` into `f(value, args)`. Anything on the right that
+    /// isn't a call is called with the value as its only argument, `value |> f`
+    /// becomes `f(value)`.
+    fn desugar_pipe(&mut self, value: Expr, func: Expr, span: Span) -> Expr {
+        return match func.kind {
+            ExprKind::Call { callee, mut args } => {
+                args.insert(0, value);
+                self.node(Expr::new(self.id(), ExprKind::Call { callee, args }, span))
+            }
+            _ => self.node(expr!(Call, self.id(), func, vec![value], span)),
+        };
+    }
+
+    /// Parses `a..b` or `a..=b`. Ranges bind looser than any binary operator and
+    /// don't chain, so `0..n + 1` ends at `n + 1`.
+    fn expr_range(&mut self) -> Result<Expr, Error> {
+        let start = self.expr_binary(Operator::Pipe.precedence())?;
+        if !matches!(self.peek().kind, Tk::DotDot | Tk::DotDotEqual) {
+            return Ok(start);
+        }
+
+        self.consume();
+        return self.range_rest(Some(start), false);
+    }
+
+    /// Parses the inside of index brackets, where a range slices the object and may
+    /// leave out its bounds, `seq[1..]`, `seq[..3]` or `seq[..]`
+    fn expr_index(&mut self) -> Result<Expr, Error> {
+        if matches!(self.current().kind, Tk::DotDot | Tk::DotDotEqual) {
+            return self.range_rest(None, true);
+        }
+
+        let index = self.expr_binary(Operator::Pipe.precedence())?;
+        if !matches!(self.peek().kind, Tk::DotDot | Tk::DotDotEqual) {
+            return Ok(index);
+        }
+
+        self.consume();
+        return self.range_rest(Some(index), true);
+    }
+
+    /// Parses the rest of a range, starting on the range operator and ending on the
+    /// last token of the end bound. Only an exclusive range that is allowed to be
+    /// open ended can go without it.
+    fn range_rest(&mut self, start: Option<Expr>, open: bool) -> Result<Expr, Error> {
+        let op = self.current_owned();
+        let inclusive = op.kind == Tk::DotDotEqual;
+
+        let mut end: Option<Expr> = None;
+        if !(open && !inclusive && self.peek_past_newlines().kind == Tk::RBrac) {
+            self.consume(); // go to start of end bound
+            end = Some(self.expr_binary(Operator::Pipe.precedence())?);
+        }
+
+        // the range sits above everything parsed for it
+        self.reach(self.reached - self.depth + 1)?;
+
+        let from = start.as_ref().map_or(op.span.start, |start| start.span.start);
+        let span = from..self.current().span.end;
+        return Ok(self.node(expr!(Range, self.id(), start, end, inclusive, span)));
     }
 
+    /// Parses an assignment, which groups from the right so `a = b = c` assigns
+    /// `b = c` to `a`. The targets are collected first and folded at the end.
     fn expr_assignment(&mut self) -> Result<Expr, Error> {
-        let mut expr = self.expr_binary()?;
+        let mut targets = Vec::<(Expr, Operator, usize)>::new();
+        let (mut expr, mut height) = self.measured(Parser::expr_range)?;
 
-        if let Some(op) = Operator::assignment(&self.peek().kind) {
+        while let Some(op) = Operator::assignment(&self.peek().kind) {
             self.consume(); // consume the operator
             self.consume(); // go to start of next expr
             self.skip_newlines();
-            let value = self.expr()?;
-            let span = expr.span.start..value.span.end;
-            expr = expr!(Binary, self.id(), expr, value, op, span);
+            targets.push((expr, op, height));
+            (expr, height) = self.measured(Parser::expr_range)?;
+        }
+
+        while let Some((assignee, op, assignee_height)) = targets.pop() {
+            height = height.max(assignee_height) + 1;
+            self.reach(height)?;
+            let span = assignee.span.start..expr.span.end;
+            expr = self.node(expr!(Assignment, self.id(), assignee, expr, op, span));
         }
 
         return Ok(expr);
@@ -406,51 +1047,654 @@ impl Parser {
 }
 
 // ----------------------------------------------------------------- \\
-// STMT PARSERS
+// TYPE PARSERS
 // ----------------------------------------------------------------- \\
 
 impl Parser {
-    fn stmt_function(&mut self) -> Result<Stmt, Error> {
-        self.assert_next(Tk::Ident, format!("expected variable name, got {}", self.peek().lexeme))?;
+    /// Parses a comma separated list of types, starting on the opening delimiter
+    /// and ending on `close`
+    fn parse_type_args(&mut self, close: Tk) -> Vec<TypeExpr> {
+        return self.parse_list(
+            close,
+            "type arguments",
+            |p| p.type_expr(),
+            |p, span| p.node(TypeExpr::new(p.id(), TypeKind::Error, span))
+        );
+    }
 
-        // start: IDENT
-        let name = self.current().lexeme.clone();
-        let start = self.current().span.start;
+    /// Parses a name with optional type arguments, `Point`, `Point{T}` or `Point<T>`.
+    /// Empty braces are left alone since `int{}` is a sequence type, and so is a '<'
+    /// that no '>' closes, since in `a is Point < b` it is a comparison.
+    fn type_named(&mut self) -> Result<TypeExpr, Error> {
+        let tk = self.current_owned();
+        let name = tk.lexeme;
 
-        self.assert_next(Tk::LParen, "expected '(' to begin function parameters".to_string())?;
-        self.consume();
+        let close = match self.peek().kind {
+            Tk::Less if self.angle_closes() => Tk::More,
+            Tk::LCurl if self.tokens.get(self.pos + 2).is_some_and(|t| t.kind != Tk::RCurl) => {
+                Tk::RCurl
+            }
+            _ => {
+                return Ok(self.node(TypeExpr::new(self.id(), TypeKind::Named { name }, tk.span)));
+            }
+        };
+
+        self.consume();
+        let args = self.parse_type_args(close);
+        let span = tk.span.start..self.current().span.end;
+        return Ok(self.node(TypeExpr::new(self.id(), TypeKind::Generic { name, args }, span)));
+    }
+
+    /// Whether the '<' after the current token is closed by a '>' with nothing but
+    /// types in between, which makes it a list of type arguments
+    fn angle_closes(&self) -> bool {
+        let mut depth = 0;
+        for tk in &self.tokens[self.pos + 1..] {
+            match tk.kind {
+                Tk::Less => depth += 1,
+                Tk::More => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return true;
+                    }
+                }
+                Tk::Ident |
+                Tk::Integer |
+                Tk::Function |
+                Tk::Comma |
+                Tk::Semicolon |
+                Tk::Question |
+                Tk::RArrow |
+                Tk::LParen |
+                Tk::RParen |
+                Tk::LBrac |
+                Tk::RBrac |
+                Tk::LCurl |
+                Tk::RCurl => {}
+                _ => {
+                    return false;
+                }
+            }
+        }
+        return false;
+    }
+
+    /// Parses `[type; size]`, starting on '[' and ending on ']'
+    fn type_array(&mut self) -> Result<TypeExpr, Error> {
+        let start = self.current().span.start;
+        self.consume(); // go to start of element type
+        let elem = self.type_expr()?;
+
+        self.assert_next(
+            Tk::Semicolon,
+            format!("expected ';' and a size after array element type, got '{}'", self.peek().lexeme)
+        )?;
+        self.consume(); // go to start of size expression
+        let size = self.expr()?;
+        self.assert_next(Tk::RBrac, "expected ']' to close array type".to_string())?;
+
+        let span = start..self.current().span.end;
+        let kind = TypeKind::Array { elem: Box::new(elem), size: Box::new(size) };
+        return Ok(self.node(TypeExpr::new(self.id(), kind, span)));
+    }
+
+    /// Parses `function(type, ...) -> type`, starting on FUNCTION and ending
+    /// on the last token of the return type or the closing ')'
+    fn type_function(&mut self) -> Result<TypeExpr, Error> {
+        let start = self.current().span.start;
+        self.assert_next(Tk::LParen, "expected '(' to begin function type parameters".to_string())?;
+        let params = self.parse_type_args(Tk::RParen);
+
+        let mut ret: Option<Box<TypeExpr>> = None;
+        if self.expect_next(Tk::RArrow) {
+            self.consume(); // go to start of return type
+            ret = Some(Box::new(self.type_expr()?));
+        }
+
+        let span = start..self.current().span.end;
+        return Ok(self.node(TypeExpr::new(self.id(), TypeKind::Function { params, ret }, span)));
+    }
+
+    /// Parses `(int, str)`, or a type in parentheses, starting on '(' and ending on ')'
+    fn type_tuple(&mut self) -> Result<TypeExpr, Error> {
+        let start = self.current().span.start;
+        let parenthesized = self.parse_parenthesized(
+            "tuple types",
+            |p| p.type_expr(),
+            |p, span| p.node(TypeExpr::new(p.id(), TypeKind::Error, span))
+        )?;
+
+        let span = start..self.current().span.end;
+        return Ok(match parenthesized {
+            Parenthesized::Group(mut ty) => {
+                ty.span = span;
+                self.node(ty)
+            }
+            Parenthesized::Tuple(elems) => self.node(TypeExpr::new(self.id(), TypeKind::Tuple { elems }, span)),
+        });
+    }
+
+    /// Parses a type expression, starting on its first token and ending on its last.
+    /// Any type can be followed by `?` to make it nullable or `{}` to make it a sequence.
+    fn type_expr(&mut self) -> Result<TypeExpr, Error> {
+        let mut ty = self.nested("type", |p| {
+            return match p.current().kind {
+                Tk::Ident => p.type_named(),
+                Tk::LBrac => p.type_array(),
+                Tk::LParen => p.type_tuple(),
+                Tk::Function => p.type_function(),
+                _ => {
+                    let tk = p.current_owned();
+                    Err(throw!(SyntaxError, tk.span, format!("expected type, got '{}'", tk.lexeme)))
+                }
+            };
+        })?;
+
+        loop {
+            let start = ty.span.start;
+            match self.peek().kind {
+                Tk::Question => {
+                    self.consume();
+                    let span = start..self.current().span.end;
+                    ty = self.node(TypeExpr::new(self.id(), TypeKind::Nullable { inner: Box::new(ty) }, span));
+                }
+                Tk::LCurl if self.tokens.get(self.pos + 2).is_some_and(|t| t.kind == Tk::RCurl) => {
+                    self.consume();
+                    self.consume();
+                    let span = start..self.current().span.end;
+                    ty = self.node(TypeExpr::new(self.id(), TypeKind::Sequence { elem: Box::new(ty) }, span));
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+
+        return Ok(ty);
+    }
+}
+
+// ----------------------------------------------------------------- \\
+// STMT PARSERS
+// ----------------------------------------------------------------- \\
+
+impl Parser {
+    fn stmt_function(&mut self) -> Result<Stmt, Error> {
+        let from = self.pos;
+        let start = self.current().span.start;
+        let header = self.function_header();
+
+        // a broken header shouldn't throw the whole body away with it, so skip
+        // to the end of the line and parse the body anyway to find its END
+        let header = match header {
+            Ok(header) => Some(header),
+            Err(err) => {
+                self.error(err);
+                if self.pos == from {
+                    self.consume();
+                }
+                self.sync();
+                None
+            }
+        };
+
+        let body = self.parse_block();
+
+        // end: END
+        let span = start..self.current().span.end;
+        let Some(header) = header else {
+            return Ok(self.node(Stmt::new(self.id(), StmtKind::Error, span)));
+        };
+
+        return Ok(self.node(stmt!(Function, self.id(), header, body, span)));
+    }
+
+    /// Parses everything from FUNCTION up to the start of the body and ends on the
+    /// first token after the header
+    fn function_header(&mut self) -> Result<FunctionHeader, Error> {
+        self.assert_next(Tk::Ident, format!("expected function name, got {}", self.peek().lexeme))?;
+
+        // start: IDENT
+        let name = self.current().lexeme.clone();
+        let mut type_params = self.type_params();
+
+        self.assert_next(Tk::LParen, "expected '(' to begin function parameters".to_string())?;
 
         // get the parameters
-        let params = self.parse_params()?;
+        let params = self.parse_params();
+
+        // the where clause can go on either side of the return type
+        let mut where_clause = Vec::<Constraint>::new();
+        if self.expect_next(Tk::Where) {
+            where_clause = self.where_clause()?;
+        }
 
         // get the return type
-        let ret: Option<Expr> = if self.expect_next_ignore_newln(Tk::RArrow) {
+        let ret: Option<TypeExpr> = if self.expect_next_ignore_newln(Tk::RArrow) {
             self.consume(); // move to start of type expression
-            Some(self.expr()?)
+            Some(self.type_expr()?)
         } else {
             None
         };
 
+        if where_clause.is_empty() && self.expect_next(Tk::Where) {
+            where_clause = self.where_clause()?;
+        }
+
+        self.declare_implicit_params(&mut type_params, &where_clause);
+        self.consume();
+        return Ok(FunctionHeader { name, type_params, params, ret, where_clause });
+    }
+
+    /// Parses an optional type parameter list after the name of a declaration. Both
+    /// `<T, U>` and `{T, U}` are accepted, the latter mirroring how types are applied
+    /// as in `Point{T}`. Starts on the name and ends on the closing delimiter, if any.
+    fn type_params(&mut self) -> Vec<TypeParam> {
+        let close = match self.peek().kind {
+            Tk::Less => Tk::More,
+            Tk::LCurl => Tk::RCurl,
+            _ => {
+                return vec![];
+            }
+        };
         self.consume();
-        self.skip_newlines();
-        let body = self.parse_block();
+
+        let params = self.parse_list(
+            close,
+            "type parameters",
+            |p| {
+                p.assert_current(
+                    Tk::Ident,
+                    format!("expected type parameter name, got '{}'", p.current().lexeme)
+                )?;
+                let name = p.current().lexeme.clone();
+                let span = p.current().span.clone();
+                Ok(Some(p.node(TypeParam { name, span, uid: p.id() })))
+            },
+            |_, _| None
+        );
+
+        let mut type_params = Vec::<TypeParam>::new();
+        for param in params.into_iter().flatten() {
+            if type_params.iter().any(|other| other.name == param.name) {
+                let msg = format!("type parameter '{}' is declared more than once", param.name);
+                self.error(throw!(SyntaxError, param.span, msg));
+            } else {
+                type_params.push(param);
+            }
+        }
+        return type_params;
+    }
+
+    /// Parses the constraints following WHERE, either wrapped in parentheses like
+    /// `where (T: Integer, U: Eq)` or running to the end of the line like `where T: Integer`.
+    /// Starts on WHERE and ends on the last token of the clause.
+    fn where_clause(&mut self) -> Result<Vec<Constraint>, Error> {
+        let mut constraints = Vec::<Constraint>::new();
+
+        if self.expect_next(Tk::LParen) {
+            let groups = self.parse_list(
+                Tk::RParen,
+                "where constraints",
+                Parser::constraint,
+                |_, _| vec![]
+            );
+            constraints.extend(groups.into_iter().flatten());
+            return Ok(constraints);
+        }
+
+        loop {
+            self.consume(); // go to start of constraint
+            constraints.extend(self.constraint()?);
+            if !self.expect_next(Tk::Comma) {
+                break;
+            }
+        }
+        return Ok(constraints);
+    }
+
+    /// Parses `T: Bound` or `T: A + B`, producing one constraint per bound
+    fn constraint(&mut self) -> Result<Vec<Constraint>, Error> {
+        self.assert_current(
+            Tk::Ident,
+            format!("expected type parameter in where clause, got '{}'", self.current().lexeme)
+        )?;
+        let param = self.current().lexeme.clone();
+        let start = self.current().span.start;
+
+        self.assert_next(
+            Tk::Colon,
+            format!("expected ':' after '{param}' in where clause, got '{}'", self.peek().lexeme)
+        )?;
+
+        let mut constraints = Vec::<Constraint>::new();
+        loop {
+            self.assert_next(
+                Tk::Ident,
+                format!("expected constraint name for '{param}', got '{}'", self.peek().lexeme)
+            )?;
+            let bound = self.current().lexeme.clone();
+            let span = start..self.current().span.end;
+            let constraint = Constraint { param: param.clone(), bound, span, uid: self.id() };
+            constraints.push(self.node(constraint));
+
+            if !self.expect_next(Tk::Plus) {
+                break;
+            }
+        }
+        return Ok(constraints);
+    }
+
+    /// Constraining a name that isn't in the type parameter list declares it, which
+    /// is what allows `function scale(x: T) where (T: Integer)` without a `<T>`
+    fn declare_implicit_params(&mut self, type_params: &mut Vec<TypeParam>, where_clause: &[Constraint]) {
+        for constraint in where_clause {
+            if type_params.iter().all(|param| param.name != constraint.param) {
+                let span = constraint.span.start..constraint.span.start + constraint.param.len();
+                type_params.push(TypeParam { name: constraint.param.clone(), span, uid: self.id() });
+            }
+        }
+    }
+
+    fn stmt_class(&mut self) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+        self.assert_next(Tk::Ident, format!("expected class name, got {}", self.peek().lexeme))?;
+
+        // start: IDENT
+        let name = self.current().lexeme.clone();
+        let from = self.pos;
+        let header = self.class_header();
+
+        // like with functions, a broken header still leaves a body to parse
+        let (type_params, where_clause) = match header {
+            Ok(header) => header,
+            Err(err) => {
+                self.error(err);
+                if self.pos == from {
+                    self.consume();
+                }
+                self.sync();
+                (vec![], vec![])
+            }
+        };
+
+        let mut fields = Vec::<FieldDecl>::new();
+        let mut methods = Vec::<Stmt>::new();
+        let mut constructor: Option<Box<Stmt>> = None;
+
+        loop {
+            self.skip_terminators();
+            if self.expect_current(Tk::End) || self.at_end() {
+                break;
+            }
+
+            match self.current().kind {
+                Tk::Function | Tk::Pub | Tk::Inline | Tk::Static => {
+                    let method = self.stmt_or_error("method", Parser::stmt_method);
+                    match &method.kind {
+                        StmtKind::Function { name: method_name, .. } if *method_name == name => {
+                            if constructor.is_some() {
+                                let msg = format!("class '{name}' already has a constructor");
+                                self.error(throw!(SyntaxError, method.span.clone(), msg));
+                            } else {
+                                constructor = Some(Box::new(method));
+                            }
+                        }
+                        _ => methods.push(method),
+                    }
+                }
+                Tk::Ident => {
+                    if let Some(field) = self.terminated("field", Parser::class_field) {
+                        fields.push(field);
+                    }
+                }
+                _ => {
+                    let tk = self.current_owned();
+                    self.error(
+                        throw!(
+                            SyntaxError,
+                            tk.span,
+                            format!("expected a field or method in class body, got '{}'", tk.lexeme)
+                        )
+                    );
+                    self.consume();
+                    self.sync();
+                }
+            }
+        }
+
+        if self.at_end() {
+            self.error(
+                throw!(SyntaxError, self.current().span.clone(), "class is missing 'end' delimiter")
+            );
+        }
+
+        let decl = ClassDecl {
+            modifiers: vec![],
+            name,
+            type_params,
+            where_clause,
+            fields,
+            methods,
+            constructor,
+        };
+        self.check_class_members(&decl);
 
         // end: END
         let span = start..self.current().span.end;
-        return Ok(stmt!(Function, self.id(), name, ret, params, body, span));
+        return Ok(self.node(Stmt::new(self.id(), StmtKind::Class(decl), span)));
     }
 
-    fn stmt_variable(&mut self) -> Result<Stmt, Error> {
-        self.assert_next(Tk::Ident, format!("expected variable name, got {}", self.peek().lexeme))?;
+    /// Parses the type parameters and where clause of a class, starting on the name
+    /// and ending on the first token after the header
+    fn class_header(&mut self) -> Result<(Vec<TypeParam>, Vec<Constraint>), Error> {
+        let mut type_params = self.type_params();
+
+        let mut where_clause = Vec::<Constraint>::new();
+        if self.expect_next(Tk::Where) {
+            where_clause = self.where_clause()?;
+        }
+
+        self.declare_implicit_params(&mut type_params, &where_clause);
+        self.consume();
+        return Ok((type_params, where_clause));
+    }
+
+    /// Parses a field declaration `name: type`, ending on the last token of the type
+    fn class_field(&mut self) -> Result<FieldDecl, Error> {
+        let name = self.current().lexeme.clone();
+        let start = self.current().span.start;
+
+        self.assert_next(
+            Tk::Colon,
+            format!("expected ':' and a type after field name, got '{}'", self.peek().lexeme)
+        )?;
+        self.consume(); // go to start of type expression
+        let ty = self.type_expr()?;
+
+        let span = start..ty.span.end;
+        return Ok(self.node(FieldDecl { name, ty, span, uid: self.id() }));
+    }
+
+    /// Fields and methods share one namespace, so every name may only be declared once
+    fn check_class_members(&mut self, decl: &ClassDecl) {
+        let mut seen = Vec::<&str>::new();
+
+        let fields = decl.fields.iter().map(|field| (field.name.as_str(), &field.span));
+        let methods = decl.methods.iter().filter_map(|method| match &method.kind {
+            StmtKind::Function { name, .. } => Some((name.as_str(), &method.span)),
+            _ => None,
+        });
+
+        for (member, span) in fields.chain(methods) {
+            if seen.contains(&member) {
+                let msg = format!("'{member}' is declared more than once in class '{}'", decl.name);
+                self.error(throw!(SyntaxError, span.clone(), msg));
+            } else {
+                seen.push(member);
+            }
+        }
+    }
+
+    fn stmt_enum(&mut self) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+        self.assert_next(Tk::Ident, format!("expected enum name, got {}", self.peek().lexeme))?;
 
         // start: IDENT
         let name = self.current().lexeme.clone();
+        self.consume();
+
+        let mut variants = Vec::<VariantDecl>::new();
+        loop {
+            self.skip_terminators();
+            if self.expect_current(Tk::End) {
+                break;
+            }
+            if self.at_end() {
+                self.error(
+                    throw!(SyntaxError, self.current().span.clone(), "enum is missing 'end' delimiter")
+                );
+                break;
+            }
+
+            if let Some(variant) = self.terminated("variant", Parser::enum_variant) {
+                if variants.iter().any(|other| other.name == variant.name) {
+                    let msg = format!("variant '{}' is declared more than once in enum '{name}'", variant.name);
+                    self.error(throw!(SyntaxError, variant.span.clone(), msg));
+                } else {
+                    variants.push(variant);
+                }
+            }
+        }
+
+        // end: END
+        let span = start..self.current().span.end;
+        return Ok(self.node(Stmt::new(self.id(), StmtKind::Enum(EnumDecl { modifiers: vec![], name, variants }), span)));
+    }
+
+    /// Parses a variant `Name` or `Name(type, ...)`, ending on the name or ')'
+    fn enum_variant(&mut self) -> Result<VariantDecl, Error> {
+        self.assert_current(
+            Tk::Ident,
+            format!("expected enum variant, got '{}'", self.current().lexeme)
+        )?;
+        let name = self.current().lexeme.clone();
+        let start = self.current().span.start;
+
+        let mut payload = Vec::<TypeExpr>::new();
+        if self.expect_next(Tk::LParen) {
+            payload = self.parse_type_args(Tk::RParen);
+        }
+
+        let span = start..self.current().span.end;
+        return Ok(self.node(VariantDecl { name, payload, span, uid: self.id() }));
+    }
+
+    /// Parses both `import path [as alias]` and `from path import items end`, where
+    /// the items are either `*` or a comma separated list that may span several lines.
+    /// Starts on IMPORT/FROM and ends on the alias, module name or END.
+    fn stmt_import(&mut self) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+        let is_from = self.expect_current(Tk::From);
+
+        let (path, path_span) = self.import_path()?;
+
+        if !is_from {
+            let mut alias: Option<String> = None;
+            if self.expect_next(Tk::As) {
+                alias = Some(self.import_alias()?);
+            }
+
+            let span = start..self.current().span.end;
+            let decl = ImportDecl { path, path_span, alias, imported: Imported::Module };
+            return Ok(self.node(Stmt::new(self.id(), StmtKind::Import(decl), span)));
+        }
+
+        self.assert_next(
+            Tk::Import,
+            format!("expected 'import' after module path, got '{}'", self.peek().lexeme)
+        )?;
+
+        let imported = if self.expect_next(Tk::Star) {
+            self.assert_next_ignore_newln(Tk::End, "expected 'end' to close import list")?;
+            Imported::Everything
+        } else {
+            let items = self.parse_list(
+                Tk::End,
+                "imported names",
+                Parser::import_item,
+                |_, _| None
+            );
+            Imported::Items(items.into_iter().flatten().collect())
+        };
+
+        let span = start..self.current().span.end;
+        let decl = ImportDecl { path, path_span, alias: None, imported };
+        return Ok(self.node(Stmt::new(self.id(), StmtKind::Import(decl), span)));
+    }
+
+    /// Parses a dotted module path, starting on the token before it and ending
+    /// on its last segment
+    fn import_path(&mut self) -> Result<(Vec<String>, Span), Error> {
+        let mut path = Vec::<String>::new();
+        let start = self.peek().span.start;
+
+        loop {
+            self.assert_next(
+                Tk::Ident,
+                format!("expected module name, got '{}'", self.peek().lexeme)
+            )?;
+            path.push(self.current().lexeme.clone());
+
+            if !self.expect_next(Tk::Dot) {
+                break;
+            }
+        }
+
+        let span = start..self.current().span.end;
+        return Ok((path, span));
+    }
+
+    /// Parses the name after AS, ending on it
+    fn import_alias(&mut self) -> Result<String, Error> {
+        self.assert_next(
+            Tk::Ident,
+            format!("expected alias after 'as', got '{}'", self.peek().lexeme)
+        )?;
+        return Ok(self.current().lexeme.clone());
+    }
+
+    /// Parses `name` or `name as alias` in an import list
+    fn import_item(&mut self) -> Result<Option<ImportItem>, Error> {
+        self.assert_current(
+            Tk::Ident,
+            format!("expected name to import, got '{}'", self.current().lexeme)
+        )?;
+        let name = self.current().lexeme.clone();
+        let span = self.current().span.clone();
+
+        let mut alias: Option<String> = None;
+        if self.expect_next(Tk::As) {
+            alias = Some(self.import_alias()?);
+        }
+
+        return Ok(Some(self.node(ImportItem { name, alias, span, uid: self.id() })));
+    }
+
+    fn stmt_variable(&mut self) -> Result<Stmt, Error> {
         let start = self.current().span.start;
+        self.consume(); // go to start of pattern
+        if self.current().lexeme == "self" && self.peek().kind == Tk::Dot {
+            return self.stmt_field_init(start);
+        }
 
-        let mut typ: Option<Expr> = None;
+        let pattern = self.pattern()?;
+        self.check_binding(&pattern, "let");
+
+        let mut typ: Option<TypeExpr> = None;
         if self.expect_next(Tk::Colon) {
-            self.consume(); // go to start of expr
-            typ = Some(self.expr_literal()?);
+            self.consume(); // go to start of type expression
+            typ = Some(self.type_expr()?);
         }
 
         self.assert_next(Tk::Equal, format!("expected '=', got {}", self.peek().lexeme))?;
@@ -460,33 +1704,501 @@ impl Parser {
         let value = self.expr()?;
 
         let span = start..value.span.end;
-        return Ok(stmt!(Variable, self.id(), name, typ, value, span));
+        return Ok(self.node(stmt!(Variable, self.id(), pattern, typ, value, span)));
     }
 
-    fn stmt(&mut self) -> Result<Stmt, Error> {
-        self.emit_diagnostics("stmt");
-        self.skip_newlines();
+    /// Parses the rest of a field initializer like `let self.x = x`, starting on SELF
+    fn stmt_field_init(&mut self, start: usize) -> Result<Stmt, Error> {
+        self.consume(); // go to DOT
+        self.assert_next(Tk::Ident, format!("expected field name after 'self.', got '{}'", self.peek().lexeme))?;
+        let name = self.current().lexeme.clone();
+        self.assert_next(Tk::Equal, format!("expected '=', got {}", self.peek().lexeme))?;
 
-        let stmt: Stmt = match &self.current().kind {
-            Tk::Let => self.stmt_variable()?,
+        self.skip_next_newlines();
+        self.consume(); // go to start of value
+        let value = self.expr()?;
+
+        let span = start..value.span.end;
+        return Ok(self.node(stmt!(FieldInit, self.id(), name, value, span)));
+    }
+
+    /// Reports the parts of a pattern that can't be bound by `let` or `for` since they
+    /// might not match, anything but names, `_` and tuples of those
+    fn check_binding(&mut self, pattern: &Pattern, keyword: &str) {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Binding { .. } | PatternKind::Error => {}
+            PatternKind::Tuple { elements } => {
+                for element in elements {
+                    self.check_binding(element, keyword);
+                }
+            }
             _ => {
-                self.emit_diagnostics("stmt parser");
-                unimplemented!()
+                let msg = format!("only names, '_' and tuples of those can be bound by '{keyword}'");
+                self.error(throw!(SyntaxError, pattern.span.clone(), msg));
+            }
+        }
+    }
+
+    /// Parses a for loop, starting on FOR and ending on END
+    fn stmt_for(&mut self) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+
+        // like with if statements, the body is still parsed if the header is broken
+        let from = self.pos;
+        let (binding, iterable) = match self.for_header() {
+            Ok(header) => header,
+            Err(err) => {
+                self.error(err);
+                if self.pos == from {
+                    self.consume();
+                }
+                self.sync();
+                let span = self.span_from(from);
+                (
+                    self.node(Pattern::new(self.id(), PatternKind::Error, span.clone())),
+                    self.node(Expr::new(self.id(), ExprKind::Error, span)),
+                )
+            }
+        };
+
+        let body = self.parse_block();
+
+        // end: END
+        let span = start..self.current().span.end;
+        return Ok(self.node(Stmt::new(self.id(), StmtKind::For { binding, iterable, body }, span)));
+    }
+
+    /// Parses `for binding in iterable do`, starting on FOR and ending on the first
+    /// token after DO
+    fn for_header(&mut self) -> Result<(Pattern, Expr), Error> {
+        self.consume(); // go to start of binding
+        let mut binding = self.pattern()?;
+
+        // `for i, s in seq` binds the same way as `for (i, s) in seq`
+        if self.peek().kind == Tk::Comma {
+            let start = binding.span.start;
+            let mut elements = vec![binding];
+            while self.expect_next(Tk::Comma) {
+                self.consume();
+                elements.push(self.pattern()?);
+            }
+            let span = start..self.current().span.end;
+            binding = self.node(Pattern::new(self.id(), PatternKind::Tuple { elements }, span));
+        }
+        self.check_binding(&binding, "for");
+
+        self.assert_next(Tk::In, format!("expected 'in' after for binding, got '{}'", self.peek().lexeme))?;
+        self.consume(); // go to start of iterable
+        let iterable = self.expr()?;
+
+        self.assert_next(
+            Tk::Do,
+            format!("expected 'do' after for iterable, got '{}'", self.peek().lexeme)
+        )?;
+        self.consume();
+        return Ok((binding, iterable));
+    }
+
+    /// Parses an if statement along with all of its `else if` and `else` branches,
+    /// which share the one END. `else if` is stored as an if statement that makes
+    /// up the whole else branch. Starts on IF and ends on END.
+    fn stmt_if(&mut self) -> Result<Stmt, Error> {
+        let depth = self.depth;
+        let result = self.if_chain(depth);
+        self.depth = depth;
+        return result;
+    }
+
+    /// Parses an if statement and its `else if` branches in a loop rather than by
+    /// recursing, nesting each `if` of the chain inside the one before at the end.
+    /// The nesting still counts, each branch is parsed one level deeper. Once that
+    /// gets too deep it is reported and the rest of the chain is parsed from `depth`
+    /// again, only to get past it.
+    fn if_chain(&mut self, depth: usize) -> Result<Stmt, Error> {
+        let mut branches = Vec::<(usize, Expr, Vec<Stmt>)>::new();
+        let mut last: Option<Vec<Stmt>> = None;
+        let mut too_deep = false;
+
+        loop {
+            let start = self.current().span.start;
+
+            // keep going with the branch even if the condition is broken
+            let from = self.pos;
+            let cond = match self.if_condition() {
+                Ok(cond) => cond,
+                Err(err) => {
+                    self.error(err);
+                    if self.pos == from {
+                        self.consume();
+                    }
+                    self.sync();
+                    self.node(Expr::new(self.id(), ExprKind::Error, self.span_from(from)))
+                }
+            };
+
+            let then = self.parse_block_until(&[Tk::End, Tk::Else]);
+            if !too_deep {
+                branches.push((start, cond, then));
+            }
+
+            if !self.expect_current(Tk::Else) {
+                break;
+            }
+            if !self.expect_next(Tk::If) {
+                self.consume();
+                let block = self.parse_block();
+                if !too_deep {
+                    last = Some(block);
+                }
+                break;
+            }
+
+            if too_deep {
+                continue;
+            }
+            // the branch itself needs a level for its condition and statements
+            if self.depth + 1 >= MAX_NESTING {
+                let err = self.too_deep();
+                self.error(err);
+                too_deep = true;
+                self.depth = depth;
+                continue;
+            }
+            self.depth += 1;
+            self.reached = self.reached.max(self.depth);
+        }
+
+        // end: END, which closes every if of the chain
+        let end = self.current().span.end;
+        let mut stmt: Option<Stmt> = None;
+        while let Some((start, cond, then)) = branches.pop() {
+            let otherwise = match stmt {
+                Some(inner) => Some(vec![inner]),
+                None => last.take(),
+            };
+            stmt = Some(self.node(stmt!(If, self.id(), cond, then, otherwise, start..end)));
+        }
+        return Ok(stmt.unwrap());
+    }
+
+    /// Parses the condition of an if statement, starting on IF and ending on the
+    /// first token after THEN
+    fn if_condition(&mut self) -> Result<Expr, Error> {
+        self.consume(); // go to start of condition
+        let cond = self.expr()?;
+        self.assert_next(
+            Tk::Then,
+            format!("expected 'then' after if condition, got '{}'", self.peek().lexeme)
+        )?;
+        self.consume();
+        return Ok(cond);
+    }
+
+    /// Parses a match statement, starting on MATCH and ending on END. Anything but
+    /// an arm in the body is reported and skipped.
+    fn stmt_match(&mut self) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+        self.consume(); // go to start of value
+
+        // like with if statements, the arms are still parsed if the value is broken
+        let from = self.pos;
+        let value = match self.expr() {
+            Ok(value) => {
+                self.consume();
+                value
+            }
+            Err(err) => {
+                self.error(err);
+                if self.pos == from {
+                    self.consume();
+                }
+                self.sync();
+                self.node(Expr::new(self.id(), ExprKind::Error, self.span_from(from)))
             }
         };
 
-        // look for end of stmt
-        let next = self.peek().kind;
-        if next != Tk::Semicolon && next != Tk::Newline && next != Tk::EOF {
+        let mut arms = Vec::<MatchArm>::new();
+        loop {
+            self.skip_terminators();
+            if self.expect_current(Tk::End) || self.at_end() {
+                break;
+            }
+
+            if self.expect_current(Tk::Case) {
+                arms.push(self.match_arm());
+                continue;
+            }
+
+            let tk = self.current_owned();
+            self.error(
+                throw!(SyntaxError, tk.span, format!("expected 'case' in match body, got '{}'", tk.lexeme))
+            );
+            self.consume();
+            self.sync();
+        }
+
+        if self.at_end() {
+            self.error(
+                throw!(SyntaxError, self.current().span.clone(), "match is missing 'end' delimiter")
+            );
+        }
+
+        // end: END
+        let span = start..self.current().span.end;
+        return Ok(self.node(Stmt::new(self.id(), StmtKind::Match { value, arms }, span)));
+    }
+
+    /// Parses an arm of a match, starting on CASE and ending on the `case` or `end`
+    /// after its body
+    fn match_arm(&mut self) -> MatchArm {
+        let start = self.current().span.start;
+
+        // like with if statements, the body is still parsed if the header is broken
+        let from = self.pos;
+        let (pattern, guard) = match self.match_arm_header() {
+            Ok(header) => header,
+            Err(err) => {
+                self.error(err);
+                if self.pos == from {
+                    self.consume();
+                }
+                self.sync();
+                (self.node(Pattern::new(self.id(), PatternKind::Error, self.span_from(from))), None)
+            }
+        };
+
+        let header_end = self.tokens[self.pos - 1].span.end;
+        let body = self.parse_block_until(&[Tk::Case, Tk::End]);
+
+        let end = body.last().map_or(header_end, |stmt| stmt.span.end);
+        let span = start..end;
+        return self.node(MatchArm { pattern, guard, body, span, uid: self.id() });
+    }
+
+    /// Parses `case pattern if guard then`, starting on CASE and ending on the first
+    /// token after THEN
+    fn match_arm_header(&mut self) -> Result<(Pattern, Option<Expr>), Error> {
+        self.consume(); // go to start of pattern
+        let pattern = self.pattern()?;
+
+        let mut guard: Option<Expr> = None;
+        if self.expect_next(Tk::If) {
+            self.consume(); // go to start of guard
+            guard = Some(self.expr()?);
+        }
+
+        self.assert_next(
+            Tk::Then,
+            format!("expected 'then' after match pattern, got '{}'", self.peek().lexeme)
+        )?;
+        self.consume();
+        return Ok((pattern, guard));
+    }
+
+    /// Parses a pattern, starting and ending on its first and last token
+    fn pattern(&mut self) -> Result<Pattern, Error> {
+        let tk = self.current_owned();
+        let kind = match tk.kind {
+            Tk::Ident if tk.lexeme == "_" => PatternKind::Wildcard,
+            Tk::Ident if tk.lexeme == "None" => PatternKind::None,
+            Tk::Ident if self.peek().kind == Tk::Dot => {
+                return self.nested("variant pattern", Parser::pattern_variant);
+            }
+            Tk::Ident => PatternKind::Binding { name: tk.lexeme },
+            Tk::Integer | Tk::Float | Tk::String | Tk::True | Tk::False => {
+                PatternKind::Literal { value: self.expr_literal()? }
+            }
+            Tk::LParen => {
+                let parenthesized = self.nested("tuple pattern", |p| {
+                    p.parse_parenthesized(
+                        "tuple patterns",
+                        |p| p.pattern(),
+                        |p, span| p.node(Pattern::new(p.id(), PatternKind::Error, span))
+                    )
+                })?;
+
+                let span = tk.span.start..self.current().span.end;
+                return Ok(match parenthesized {
+                    Parenthesized::Group(mut pattern) => {
+                        pattern.span = span;
+                        self.node(pattern)
+                    }
+                    Parenthesized::Tuple(elements) => {
+                        self.node(Pattern::new(self.id(), PatternKind::Tuple { elements }, span))
+                    }
+                });
+            }
+            _ => {
+                return Err(
+                    throw!(SyntaxError, tk.span, format!("expected a pattern, got '{}'", tk.lexeme))
+                );
+            }
+        };
+
+        return Ok(self.node(Pattern::new(self.id(), kind, tk.span)));
+    }
+
+    /// Parses `Enum.Variant` with optional payload patterns, starting on the enum
+    /// name and ending on the variant or ')'
+    fn pattern_variant(&mut self) -> Result<Pattern, Error> {
+        let start = self.current().span.start;
+        let enum_name = self.current().lexeme.clone();
+
+        self.consume();
+        self.assert_next(
+            Tk::Ident,
+            format!("expected variant of '{enum_name}' after '.', got '{}'", self.peek().lexeme)
+        )?;
+        let variant = self.current().lexeme.clone();
+
+        let mut fields = Vec::<Pattern>::new();
+        if self.expect_next(Tk::LParen) {
+            fields = self.parse_list(
+                Tk::RParen,
+                "variant patterns",
+                |p| p.pattern(),
+                |p, span| p.node(Pattern::new(p.id(), PatternKind::Error, span))
+            );
+        }
+
+        let span = start..self.current().span.end;
+        let kind = PatternKind::Variant { enum_name, variant, fields };
+        return Ok(self.node(Pattern::new(self.id(), kind, span)));
+    }
+
+    fn stmt_return(&mut self) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+
+        let mut value: Option<Expr> = None;
+        if !Self::ends_stmt(self.peek().kind) {
+            self.consume(); // go to start of value
+            value = Some(self.expr()?);
+        }
+
+        let span = start..self.current().span.end;
+        return Ok(self.node(Stmt::new(self.id(), StmtKind::Return { value }, span)));
+    }
+
+    fn stmt_expression(&mut self) -> Result<Stmt, Error> {
+        let expr = self.expr()?;
+        let span = expr.span.clone();
+        return Ok(self.node(stmt!(Expression, self.id(), expr, span)));
+    }
+
+    /// Parses the modifiers in front of a declaration, starting on the first one
+    /// and ending on the declaration keyword. Duplicates are reported and dropped.
+    fn modifiers(&mut self) -> Vec<(Modifier, Span)> {
+        let mut modifiers = Vec::<(Modifier, Span)>::new();
+
+        while let Some(modifier) = Modifier::from_token(&self.current().kind) {
+            let span = self.current().span.clone();
+            if modifiers.iter().any(|(other, _)| *other == modifier) {
+                self.error(throw!(SyntaxError, span, format!("duplicate '{modifier}' modifier")));
+            } else {
+                modifiers.push((modifier, span));
+            }
+            self.consume();
+        }
+
+        return modifiers;
+    }
+
+    /// Parses a declaration with modifiers in front of it, `allowed` being the
+    /// declaration keywords accepted in this position. Modifiers that cannot be
+    /// used on the declaration are reported but the declaration is still kept.
+    fn declaration(&mut self, allowed: &[Tk]) -> Result<Stmt, Error> {
+        let start = self.current().span.start;
+        let modifiers = self.modifiers();
+
+        // start: declaration keyword
+        let target = self.current_owned();
+        if !allowed.contains(&target.kind) {
             return Err(
                 throw!(
                     SyntaxError,
-                    self.current().span.clone(),
-                    "expected ';' or new line to complete statement"
+                    target.span,
+                    format!("expected a declaration after modifiers, got '{}'", target.lexeme)
                 )
             );
         }
-        self.consume();
+
+        for (modifier, span) in &modifiers {
+            if !modifier.allowed_on(&target.kind) {
+                let msg = format!("'{modifier}' cannot be used on '{}' declarations", target.lexeme);
+                self.error(throw!(SyntaxError, span.clone(), msg));
+            }
+        }
+
+        let mut stmt = match target.kind {
+            Tk::Function => self.stmt_function()?,
+            Tk::Class => self.stmt_class()?,
+            Tk::Enum => self.stmt_enum()?,
+            _ => self.stmt_variable()?,
+        };
+
+        if let StmtKind::FieldInit { .. } = stmt.kind {
+            for (modifier, span) in &modifiers {
+                let msg = format!("'{modifier}' cannot be used on field initializers");
+                self.error(throw!(SyntaxError, span.clone(), msg));
+            }
+        }
+
+        let modifiers = modifiers
+            .into_iter()
+            .filter(|(modifier, _)| modifier.allowed_on(&target.kind))
+            .map(|(modifier, _)| modifier)
+            .collect();
+
+        match &mut stmt.kind {
+            StmtKind::Function { modifiers: slot, .. } => *slot = modifiers,
+            StmtKind::Variable { modifiers: slot, .. } => *slot = modifiers,
+            StmtKind::Class(decl) => decl.modifiers = modifiers,
+            StmtKind::Enum(decl) => decl.modifiers = modifiers,
+            _ => {}
+        }
+
+        stmt.span.start = start;
+        return Ok(self.node(stmt));
+    }
+
+    fn stmt_declaration(&mut self) -> Result<Stmt, Error> {
+        return self.declaration(&[Tk::Function, Tk::Class, Tk::Enum, Tk::Let]);
+    }
+
+    /// Parses a method of a class body, which may also have modifiers
+    fn stmt_method(&mut self) -> Result<Stmt, Error> {
+        return self.declaration(&[Tk::Function]);
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, Error> {
+        self.skip_newlines();
+
+        let stmt: Stmt = match &self.current().kind {
+            Tk::Let => self.stmt_variable()?,
+            Tk::Return => self.stmt_return()?,
+            Tk::If => self.stmt_if()?,
+            Tk::Match => self.stmt_match()?,
+            Tk::For => self.stmt_for()?,
+
+            // named functions can be nested, `function(` starts a lambda instead
+            Tk::Function if self.peek().kind != Tk::LParen => self.stmt_function()?,
+
+            Tk::End => {
+                return Err(
+                    throw!(SyntaxError, self.current().span.clone(), "'end' does not close any block")
+                );
+            }
+            Tk::Pub | Tk::Inline | Tk::Static => {
+                return Err(
+                    throw!(
+                        SyntaxError,
+                        self.current().span.clone(),
+                        "modifiers are only allowed on top-level declarations"
+                    )
+                );
+            }
+            _ => self.stmt_expression()?,
+        };
 
         return Ok(stmt);
     }
@@ -500,24 +2212,20 @@ impl Parser {
     fn parse_program(&mut self) -> (Vec<Stmt>, ErrorBuffer) {
         let mut stmts: Vec<Stmt> = vec![];
 
-        while !self.at_end() {
-            match &self.current().kind {
-                Tk::Function =>
-                    match self.stmt_function() {
-                        Ok(stmt) => stmts.push(stmt),
-                        Err(err) => {
-                            self.error(err);
-                            self.sync_after_fn();
-                        }
-                    }
-                _ => {
-                    self.emit_diagnostics("parse program");
-                    unimplemented!();
-                }
+        loop {
+            self.skip_terminators();
+            if self.at_end() {
+                break;
             }
 
-            self.consume();
-            self.skip_newlines();
+            let stmt = match &self.current().kind {
+                Tk::Class => self.stmt_or_error("class", Parser::stmt_class),
+                Tk::Enum => self.stmt_or_error("enum", Parser::stmt_enum),
+                Tk::Import | Tk::From => self.stmt_or_error("import", Parser::stmt_import),
+                Tk::Pub | Tk::Inline | Tk::Static => self.stmt_or_error("declaration", Parser::stmt_declaration),
+                _ => self.stmt_or_error("stmt", Parser::stmt),
+            };
+            stmts.push(stmt);
         }
 
         return (stmts, self.dump_errors());
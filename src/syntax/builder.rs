@@ -0,0 +1,100 @@
+use std::rc::Rc;
+use crate::{ common::span::Span, lexer::token::{ Tk, Token }, parser::ast::NodeId };
+use super::green::{ GreenElement, GreenNode, GreenToken, SyntaxKind };
+
+/// Builds the green tree while the parser runs. Tokens are added as the parser
+/// reaches them, along with the trivia in front of them, and every node the parser
+/// finishes gathers the tokens and nodes it was parsed from.
+pub struct SyntaxBuilder {
+    source: String,
+
+    /// The whole token stream, comments and whitespace included
+    tokens: Vec<Token>,
+
+    /// The first of `tokens` that isn't in the tree yet
+    next: usize,
+
+    /// How far into the source the tree reaches
+    end: usize,
+
+    /// Everything not gathered into a node yet, next to the offset each starts at
+    pending: Vec<(usize, GreenElement)>,
+}
+
+impl SyntaxBuilder {
+    /// Starts a tree over `source`, which `tokens` has to cover the way
+    /// [`Lexer::with_trivia`](crate::lexer::lexer::Lexer::with_trivia) does
+    pub fn new(source: &str, tokens: Vec<Token>) -> SyntaxBuilder {
+        return SyntaxBuilder { source: source.to_string(), tokens, next: 0, end: 0, pending: vec![] };
+    }
+
+    /// Adds the tokens up to `token`, a token of the parser's stream, along with
+    /// the trivia in between
+    pub fn token(&mut self, token: &Token) {
+        while let Some(next) = self.tokens.get(self.next) && next.span.start < token.span.end {
+            let (kind, span) = (next.kind, next.span.clone());
+            self.next += 1;
+            self.push(kind, span);
+        }
+    }
+
+    /// Gathers everything added since `span` started into a node of `kind`. A node
+    /// that was already built for the same id is taken apart, so a node whose span
+    /// grows after it was finished ends up in the tree once.
+    pub fn node(&mut self, kind: SyntaxKind, id: NodeId, span: &Span) {
+        let mut first = self.pending.len();
+        while first > 0 && self.pending[first - 1].0 >= span.start {
+            first -= 1;
+        }
+        let mut last = self.pending.len();
+        while last > first && self.pending[last - 1].0 >= span.end {
+            last -= 1;
+        }
+
+        let after = self.pending.split_off(last);
+        let inside = self.pending.split_off(first);
+        let start = inside.first().map_or(span.start, |(start, _)| *start);
+
+        let mut children = vec![];
+        for (_, child) in inside {
+            match child {
+                GreenElement::Node(node) if node.id == Some(id) => children.extend(node.children.iter().cloned()),
+                child => children.push(child),
+            }
+        }
+
+        let node = GreenNode::new(kind, Some(id), children);
+        self.pending.push((start, GreenElement::Node(Rc::new(node))));
+        self.pending.extend(after);
+    }
+
+    /// Adds whatever the parser never reached and gives back the root of the tree
+    pub fn finish(mut self) -> GreenNode {
+        while let Some(next) = self.tokens.get(self.next) {
+            let (kind, span) = (next.kind, next.span.clone());
+            self.next += 1;
+            self.push(kind, span);
+        }
+        let len = self.source.len();
+        self.push(Tk::EOF, len..len);
+
+        let children = self.pending.into_iter().map(|(_, child)| child).collect();
+        return GreenNode::new(SyntaxKind::Root, None, children);
+    }
+
+    fn push(&mut self, kind: Tk, span: Span) {
+        // whatever the lexer threw away still has to be in the tree
+        if span.start > self.end {
+            let text = &self.source[self.end..span.start];
+            let token = GreenToken::new(Tk::Unknown, text);
+            self.pending.push((self.end, GreenElement::Token(Rc::new(token))));
+        }
+
+        if kind != Tk::EOF {
+            let text = &self.source[span.clone()];
+            let token = GreenToken::new(kind, text);
+            self.pending.push((span.start, GreenElement::Token(Rc::new(token))));
+        }
+        self.end = self.end.max(span.end);
+    }
+}
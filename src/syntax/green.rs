@@ -0,0 +1,140 @@
+use std::rc::Rc;
+use crate::{ lexer::token::Tk, parser::ast::NodeId };
+
+/// What a syntax tree node stands for. The parser builds a node for every node of
+/// the AST as it goes, of the kind of construct it parsed, and the node keeps the
+/// id of its AST node so the two can be mapped onto each other.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SyntaxKind {
+    Root,
+
+    // statements
+    VariableStmt,
+    FunctionStmt,
+    ClassStmt,
+    EnumStmt,
+    ImportStmt,
+    ReturnStmt,
+    IfStmt,
+    MatchStmt,
+    ForStmt,
+    ExprStmt,
+    FieldInitStmt,
+    ErrorStmt,
+
+    // expressions
+    IntegerExpr,
+    FloatExpr,
+    StringExpr,
+    BooleanExpr,
+    IdentExpr,
+    ArrayLiteralExpr,
+    ArrayRepeatExpr,
+    SeqLiteralExpr,
+    TupleExpr,
+    CallExpr,
+    FieldExpr,
+    IndexExpr,
+    RangeExpr,
+    VariantExpr,
+    TypeApplyExpr,
+    AssignmentExpr,
+    ParameterExpr,
+    LambdaExpr,
+    BinaryExpr,
+    IsExpr,
+    DefaultExpr,
+    ErrorExpr,
+
+    // types
+    NamedType,
+    GenericType,
+    NullableType,
+    ArrayType,
+    SequenceType,
+    TupleType,
+    FunctionType,
+    ErrorType,
+
+    // patterns
+    WildcardPattern,
+    NonePattern,
+    BindingPattern,
+    LiteralPattern,
+    TuplePattern,
+    VariantPattern,
+    ErrorPattern,
+
+    // parts of declarations and statements
+    MatchArm,
+    FieldDecl,
+    TypeParam,
+    Constraint,
+    VariantDecl,
+    ImportItem,
+}
+
+/// The immutable, position independent half of the tree. A green node only knows
+/// its width, positions are worked out by the syntax nodes on top of it.
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+    pub kind: SyntaxKind,
+    pub id: Option<NodeId>,
+    pub width: usize,
+    pub children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, id: Option<NodeId>, children: Vec<GreenElement>) -> GreenNode {
+        let width = children.iter().map(GreenElement::width).sum();
+        return GreenNode { kind, id, width, children };
+    }
+
+    /// The source text covered by the node, trivia included
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.width);
+        self.write(&mut text);
+        return text;
+    }
+
+    fn write(&self, text: &mut String) {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write(text),
+                GreenElement::Token(token) => text.push_str(&token.text),
+            }
+        }
+    }
+}
+
+/// A token along with its exact text, whitespace and comments are tokens too
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+    pub kind: Tk,
+    pub text: String,
+}
+
+impl GreenToken {
+    pub fn new(kind: Tk, text: &str) -> GreenToken {
+        return GreenToken { kind, text: text.to_string() };
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        return matches!(self.kind, Tk::Whitespace | Tk::Comment);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        return match self {
+            GreenElement::Node(node) => node.width,
+            GreenElement::Token(token) => token.text.len(),
+        };
+    }
+}
@@ -0,0 +1,53 @@
+pub mod builder;
+pub mod green;
+pub mod tree;
+pub mod typed;
+
+use std::rc::Rc;
+use crate::{
+    analysis::index::NodeIndex,
+    common::errors::ErrorBuffer,
+    lexer::{ lexer::Lexer, token::{ Tk, Token } },
+    parser::{ ast::{ NodeId, Stmt }, parser::Parser },
+};
+use tree::SyntaxNode;
+
+/// Everything known about a file after parsing it: the lossless syntax tree, the
+/// AST as the typed view over it, and whatever errors were found on the way. The
+/// parser builds both at once, every AST node written out in the source has a
+/// syntax node with its id.
+pub struct Parse {
+    pub root: SyntaxNode,
+    pub ast: Vec<Stmt>,
+    pub index: NodeIndex,
+    pub errors: ErrorBuffer,
+}
+
+impl Parse {
+    /// The syntax node standing for an AST node
+    pub fn syntax(&self, id: NodeId) -> Option<SyntaxNode> {
+        return self.root.find(id);
+    }
+}
+
+/// Parses `source` into a syntax tree that holds every byte of it, comments,
+/// whitespace and rejected characters included, so `root.text()` gives the source
+/// back. Broken code still gets a tree, the tokens the parser couldn't place
+/// simply end up under the closest node around them.
+pub fn parse_file(source: &str) -> Parse {
+    let (tokens, mut errors) = Lexer::with_trivia(source).lex();
+    let code = tokens
+        .iter()
+        .filter(|tk| !matches!(tk.kind, Tk::Comment | Tk::Whitespace))
+        .map(Token::copy)
+        .collect();
+
+    let mut parser = Parser::new(code);
+    parser.keep_syntax(source, tokens);
+    let (ast, parse_errors) = parser.parse();
+    errors.extend(parse_errors);
+
+    let green = parser.syntax().unwrap();
+    let index = NodeIndex::build(&ast);
+    return Parse { root: SyntaxNode::root(Rc::new(green)), ast, index, errors };
+}
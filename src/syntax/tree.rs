@@ -0,0 +1,151 @@
+use std::rc::Rc;
+use crate::{ common::span::Span, parser::ast::NodeId };
+use super::green::{ GreenElement, GreenNode, GreenToken, SyntaxKind };
+
+/// The positioned half of the tree, built on demand over the green nodes. A syntax
+/// node knows its offset in the file and its parent, which makes it cheap to
+/// navigate in every direction while the green tree underneath stays shared.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode(Rc<RedNode>);
+
+#[derive(Debug)]
+struct RedNode {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+
+    /// Position among the children of the parent, used to rebuild it on edits
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    pub green: Rc<GreenToken>,
+    pub parent: SyntaxNode,
+    pub offset: usize,
+}
+
+impl SyntaxToken {
+    pub fn span(&self) -> Span {
+        return self.offset..self.offset + self.green.text.len();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn root(green: Rc<GreenNode>) -> SyntaxNode {
+        return SyntaxNode(Rc::new(RedNode { green, parent: None, index: 0, offset: 0 }));
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        return &self.0.green;
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        return self.0.green.kind;
+    }
+
+    /// The id of the AST node this node stands for
+    pub fn id(&self) -> Option<NodeId> {
+        return self.0.green.id;
+    }
+
+    pub fn span(&self) -> Span {
+        return self.0.offset..self.0.offset + self.0.green.width;
+    }
+
+    pub fn text(&self) -> String {
+        return self.0.green.text();
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        return self.0.parent.clone();
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut elements = vec![];
+        for (index, child) in self.0.green.children.iter().enumerate() {
+            elements.push(match child {
+                GreenElement::Node(green) => {
+                    SyntaxElement::Node(
+                        SyntaxNode(
+                            Rc::new(RedNode { green: green.clone(), parent: Some(self.clone()), index, offset })
+                        )
+                    )
+                }
+                GreenElement::Token(green) => {
+                    SyntaxElement::Token(SyntaxToken { green: green.clone(), parent: self.clone(), offset })
+                }
+            });
+            offset += child.width();
+        }
+        return elements;
+    }
+
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        return self
+            .children_with_tokens()
+            .into_iter()
+            .filter_map(|element| if let SyntaxElement::Node(node) = element { Some(node) } else { None })
+            .collect();
+    }
+
+    /// The tokens directly under this node, not those of its children
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        return self
+            .children_with_tokens()
+            .into_iter()
+            .filter_map(|element| if let SyntaxElement::Token(token) = element { Some(token) } else { None })
+            .collect();
+    }
+
+    /// This node and everything below it, parents before their children
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut nodes = vec![self.clone()];
+        for child in self.children() {
+            nodes.extend(child.descendants());
+        }
+        return nodes;
+    }
+
+    /// The node standing for the AST node with the given id
+    pub fn find(&self, id: NodeId) -> Option<SyntaxNode> {
+        if self.id() == Some(id) {
+            return Some(self.clone());
+        }
+        return self
+            .children()
+            .into_iter()
+            .find_map(|child| child.find(id));
+    }
+
+    /// The innermost node covering the byte at `offset`, or this one if none of
+    /// its children do
+    pub fn covering(&self, offset: usize) -> SyntaxNode {
+        return match self.children().into_iter().find(|child| child.span().contains(&offset)) {
+            Some(child) => child.covering(offset),
+            None => self.clone(),
+        };
+    }
+
+    /// Swaps this node for `replacement` and gives back the root of the new tree.
+    /// Only the nodes on the way up to the root are rebuilt, the rest is shared with
+    /// the old tree, and the text outside of this node stays exactly as it was.
+    pub fn replace_with(&self, replacement: Rc<GreenNode>) -> SyntaxNode {
+        let mut green = replacement;
+        let mut node = self.clone();
+        while let Some(parent) = node.parent() {
+            let mut children = parent.green().children.clone();
+            children[node.0.index] = GreenElement::Node(green);
+            green = Rc::new(GreenNode::new(parent.kind(), parent.id(), children));
+            node = parent;
+        }
+        return SyntaxNode::root(green);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    common::span::Span,
+    parser::ast::{
+        Constraint,
+        Expr,
+        ExprKind,
+        FieldDecl,
+        ImportItem,
+        MatchArm,
+        NodeId,
+        Pattern,
+        PatternKind,
+        Stmt,
+        StmtKind,
+        TypeExpr,
+        TypeKind,
+        TypeParam,
+        VariantDecl,
+    },
+};
+use super::green::SyntaxKind;
+
+/// A node of the AST, which is a typed view of the syntax node with the same id
+pub trait AstNode {
+    fn syntax_kind(&self) -> SyntaxKind;
+    fn uid(&self) -> NodeId;
+    fn span(&self) -> &Span;
+}
+
+impl AstNode for Stmt {
+    fn syntax_kind(&self) -> SyntaxKind {
+        return match &self.kind {
+            StmtKind::Variable { .. } => SyntaxKind::VariableStmt,
+            StmtKind::Function { .. } => SyntaxKind::FunctionStmt,
+            StmtKind::Class(_) => SyntaxKind::ClassStmt,
+            StmtKind::Enum(_) => SyntaxKind::EnumStmt,
+            StmtKind::Import(_) => SyntaxKind::ImportStmt,
+            StmtKind::Return { .. } => SyntaxKind::ReturnStmt,
+            StmtKind::If { .. } => SyntaxKind::IfStmt,
+            StmtKind::Match { .. } => SyntaxKind::MatchStmt,
+            StmtKind::For { .. } => SyntaxKind::ForStmt,
+            StmtKind::Expression { .. } => SyntaxKind::ExprStmt,
+            StmtKind::FieldInit { .. } => SyntaxKind::FieldInitStmt,
+            StmtKind::Error => SyntaxKind::ErrorStmt,
+        };
+    }
+
+    fn uid(&self) -> NodeId {
+        return self.uid;
+    }
+
+    fn span(&self) -> &Span {
+        return &self.span;
+    }
+}
+
+impl AstNode for Expr {
+    fn syntax_kind(&self) -> SyntaxKind {
+        return match &self.kind {
+            ExprKind::Integer { .. } => SyntaxKind::IntegerExpr,
+            ExprKind::Float { .. } => SyntaxKind::FloatExpr,
+            ExprKind::String { .. } => SyntaxKind::StringExpr,
+            ExprKind::Boolean { .. } => SyntaxKind::BooleanExpr,
+            ExprKind::Ident { .. } => SyntaxKind::IdentExpr,
+            ExprKind::ArrayLiteral { .. } => SyntaxKind::ArrayLiteralExpr,
+            ExprKind::ArrayRepeat { .. } => SyntaxKind::ArrayRepeatExpr,
+            ExprKind::SeqLiteral { .. } => SyntaxKind::SeqLiteralExpr,
+            ExprKind::Tuple { .. } => SyntaxKind::TupleExpr,
+            ExprKind::Call { .. } => SyntaxKind::CallExpr,
+            ExprKind::Field { .. } => SyntaxKind::FieldExpr,
+            ExprKind::Index { .. } => SyntaxKind::IndexExpr,
+            ExprKind::Range { .. } => SyntaxKind::RangeExpr,
+            ExprKind::Variant { .. } => SyntaxKind::VariantExpr,
+            ExprKind::TypeApply { .. } => SyntaxKind::TypeApplyExpr,
+            ExprKind::Assignment { .. } => SyntaxKind::AssignmentExpr,
+            ExprKind::Parameter { .. } => SyntaxKind::ParameterExpr,
+            ExprKind::Lambda { .. } => SyntaxKind::LambdaExpr,
+            ExprKind::Binary { .. } => SyntaxKind::BinaryExpr,
+            ExprKind::Is { .. } => SyntaxKind::IsExpr,
+            ExprKind::Default => SyntaxKind::DefaultExpr,
+            ExprKind::Error => SyntaxKind::ErrorExpr,
+        };
+    }
+
+    fn uid(&self) -> NodeId {
+        return self.uid;
+    }
+
+    fn span(&self) -> &Span {
+        return &self.span;
+    }
+}
+
+impl AstNode for TypeExpr {
+    fn syntax_kind(&self) -> SyntaxKind {
+        return match &self.kind {
+            TypeKind::Named { .. } => SyntaxKind::NamedType,
+            TypeKind::Generic { .. } => SyntaxKind::GenericType,
+            TypeKind::Nullable { .. } => SyntaxKind::NullableType,
+            TypeKind::Array { .. } => SyntaxKind::ArrayType,
+            TypeKind::Sequence { .. } => SyntaxKind::SequenceType,
+            TypeKind::Tuple { .. } => SyntaxKind::TupleType,
+            TypeKind::Function { .. } => SyntaxKind::FunctionType,
+            TypeKind::Error => SyntaxKind::ErrorType,
+        };
+    }
+
+    fn uid(&self) -> NodeId {
+        return self.uid;
+    }
+
+    fn span(&self) -> &Span {
+        return &self.span;
+    }
+}
+
+impl AstNode for Pattern {
+    fn syntax_kind(&self) -> SyntaxKind {
+        return match &self.kind {
+            PatternKind::Wildcard => SyntaxKind::WildcardPattern,
+            PatternKind::None => SyntaxKind::NonePattern,
+            PatternKind::Binding { .. } => SyntaxKind::BindingPattern,
+            PatternKind::Literal { .. } => SyntaxKind::LiteralPattern,
+            PatternKind::Tuple { .. } => SyntaxKind::TuplePattern,
+            PatternKind::Variant { .. } => SyntaxKind::VariantPattern,
+            PatternKind::Error => SyntaxKind::ErrorPattern,
+        };
+    }
+
+    fn uid(&self) -> NodeId {
+        return self.uid;
+    }
+
+    fn span(&self) -> &Span {
+        return &self.span;
+    }
+}
+
+/// The parts of declarations and statements that have ids of their own
+macro_rules! ast_node {
+    ($node:ident) => {
+        impl AstNode for $node {
+            fn syntax_kind(&self) -> SyntaxKind {
+                return SyntaxKind::$node;
+            }
+
+            fn uid(&self) -> NodeId {
+                return self.uid;
+            }
+
+            fn span(&self) -> &Span {
+                return &self.span;
+            }
+        }
+    };
+}
+
+ast_node!(MatchArm);
+ast_node!(FieldDecl);
+ast_node!(TypeParam);
+ast_node!(Constraint);
+ast_node!(VariantDecl);
+ast_node!(ImportItem);
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use crate::{ dump, emit::{ dot, json, sexp }, lex, parse, parser::{ ast::Stmt, parser::MAX_NESTING }, Emit };
+
+    fn parse_clean(source: &str) -> Vec<Stmt> {
+        let (tokens, _) = lex(source);
+        let (ast, errors) = parse(tokens);
+        assert!(errors.is_empty(), "unexpected errors in:\n{source}\n{errors:#?}");
+        return ast;
+    }
+
+    #[test]
+    fn tokens_as_json() {
+        let (tokens, _) = lex("x \"a\\\"b\"");
+        let expected = "[\n  {\n    \"kind\": \"Ident\",\n    \"span\": [0, 1],\n    \"lexeme\": \"x\"\n  },\n  {\n    \"kind\": \"String\",\n    \"span\": [2, 8],\n    \"lexeme\": \"a\\\"b\"\n  },\n  {\n    \"kind\": \"EOF\",\n    \"span\": [8, 8],\n    \"lexeme\": \"<EOF>\"\n  }\n]\n";
+        assert_eq!(json::tokens(&tokens), expected);
+    }
+
+    #[test]
+    fn ast_as_json() {
+        let ast = parse_clean("return a + 1.5");
+        let expected = "[\n  {\n    \"kind\": \"Return\",\n    \"span\": [0, 14],\n    \"uid\": 3,\n    \"value\": {\n      \"kind\": \"Binary\",\n      \"span\": [7, 14],\n      \"uid\": 2,\n      \"op\": \"+\",\n      \"lhs\": {\n        \"kind\": \"Ident\",\n        \"span\": [7, 8],\n        \"uid\": 0,\n        \"name\": \"a\"\n      },\n      \"rhs\": {\n        \"kind\": \"Float\",\n        \"span\": [11, 14],\n        \"uid\": 1,\n        \"value\": 1.5\n      }\n    }\n  }\n]\n";
+        assert_eq!(json::ast(&ast), expected);
+        assert_eq!(json::ast(&[]), "[]\n");
+    }
+
+    #[test]
+    fn ast_as_sexp() {
+        let cases = [
+            ("let (a, _): int? = f(1)", "(Variable [] (Tuple [(Binding a) (Wildcard)]) (Nullable (Named int)) (Call (Ident f) [(Integer 1)]))"),
+            ("x += seq[..3]", "(Expression (Assignment += (Ident x) (Index (Ident seq) (Range nil (Integer 3) false))))"),
+            ("pub function f{T}(a: T) where T: Eq\nend", "(Function [pub] f [(TypeParam T)] [(Parameter a (Named T))] nil [(Constraint T Eq)] [])"),
+            ("from m import a as b end", "(Import (ImportDecl [m] nil [(ImportItem a b)]))"),
+            ("match s\ncase Shape.Circle(r) if r > 0 then\nreturn \"big\\n\"\nend", "(Match (Ident s) [(MatchArm (Variant Shape Circle [(Binding r)]) (Binary > (Ident r) (Integer 0)) [(Return (String \"big\\n\"))])])"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(sexp::ast(&parse_clean(source)), format!("{expected}\n"), "for {source}");
+        }
+    }
+
+    #[test]
+    fn ast_as_dot() {
+        let graph = dot::ast(&parse_clean("pub let s = \"q\\\"\" * (1 + b)"));
+        let expected = [
+            r#"digraph ast {"#,
+            r#"n0 [label="program"];"#,
+            r#"n1 [label="Variable\nmodifiers: pub\n0..27"];"#,
+            r#"n3 [label="Binary\nop: *\n12..27"];"#,
+            r#"n4 [label="String\nvalue: \"q\\\"\"\n12..17"];"#,
+            r#"n3 -> n5 [label="rhs"];"#,
+            r#"n0 -> n1 [label="[0]"];"#,
+        ];
+        for line in expected {
+            assert!(graph.contains(line), "missing {line} in:\n{graph}");
+        }
+        assert!(graph.ends_with("}\n"));
+    }
+
+    #[test]
+    fn long_chains_dump_without_overflowing() {
+        fn dump_all(source: &str) -> Vec<String> {
+            let emits = [Emit::Tokens, Emit::Ast, Emit::Sexp, Emit::Dot];
+            return emits.into_iter().map(|emit| format!("{:?}", dump(source, emit, None).1)).collect();
+        }
+
+        let within = vec!["a"; MAX_NESTING - 10].join(" + ");
+        for errors in dump_all(&format!("let x = {within}\n")) {
+            assert_eq!(errors, "[]");
+        }
+
+        let terms = vec!["a"; 10_000];
+        let sources = [
+            format!("let x = {}\n", terms.join(" + ")),
+            format!("let x = a{}\n", ".f()".repeat(10_000)),
+            format!("{}\n", terms.join(" = ")),
+            format!("if a then\n{}end\n", "else if a then\n".repeat(10_000)),
+        ];
+        // the passes after the parser get the stack of a main thread, like in kaska
+        let checks = move || {
+            for source in sources {
+                for errors in dump_all(&source) {
+                    assert!(errors.contains("nested too deeply"), "no nesting error: {errors}");
+                }
+            }
+        };
+        let thread = std::thread::Builder::new().stack_size(8 << 20).spawn(checks).unwrap();
+        thread.join().unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        emit::printer::print,
+        format::formatter::format,
+        lex,
+        lexer::{ lexer::Lexer, token::Tk },
+        parse,
+        tests::{ Gen, Rng },
+    };
+
+    fn formatted(source: &str) -> String {
+        let out = format(source).expect("source should format");
+        assert_eq!(format(&out).unwrap(), out, "formatting is not idempotent for:\n{source}");
+        return out;
+    }
+
+    #[test]
+    fn trivia_tokens_cover_the_source() {
+        let source = "let x = 1 # one\n\tif x then  # check\nend\n".to_string();
+        let (tokens, errors) = Lexer::with_trivia(&source).lex();
+        assert!(errors.is_empty());
+
+        let rebuilt: String = tokens.iter().map(|tk| &source[tk.span.clone()]).collect();
+        assert_eq!(rebuilt, source);
+        assert!(tokens.iter().any(|tk| tk.kind == Tk::Comment && tk.lexeme == "# one"));
+
+        // comments no longer swallow the newline that ends their line
+        let (tokens, _) = lex(&source);
+        assert_eq!(tokens.iter().filter(|tk| tk.kind == Tk::Newline).count(), 3);
+    }
+
+    #[test]
+    fn normalizes_layout_and_keeps_comments() {
+        let source = "\n\n# leading comment\nlet   x=1+2 # trailing\nfunction f( a:int )->int\n\n\n  if a>x then\n        return a\n   else\nreturn [ x ; 2 ][ 0 ]\n      end\nend\n# about g\nfunction g{T}( )\nmatch x\n  case 1 then\nf(1)\n case _ then\nend\nend\nlet y = f(2)\n\n\n";
+        let expected = "# leading comment\nlet x = 1 + 2 # trailing\n\nfunction f(a: int) -> int\n    if a > x then\n        return a\n    else\n        return [x; 2][0]\n    end\nend\n\n# about g\nfunction g{T}()\n    match x\n        case 1 then\n            f(1)\n        case _ then\n    end\nend\n\nlet y = f(2)\n";
+        assert_eq!(formatted(source), expected);
+    }
+
+    #[test]
+    fn function_types_and_lambdas() {
+        let source = "let t :function( int )->bool=function(y)\nreturn y>0\nend\nlet p: Point<int> = q < r\n";
+        let expected = "let t: function(int) -> bool = function(y)\n    return y > 0\nend\nlet p: Point<int> = q < r\n";
+        assert_eq!(formatted(source), expected);
+    }
+
+    #[test]
+    fn broken_source_is_not_formatted() {
+        assert!(format("let = 1\n").is_err());
+        assert!(format("let s = \"open\n").is_err());
+    }
+
+    /// Re-lays out `source` with random amounts of whitespace between tokens and at
+    /// the start of lines, without changing what it lexes to
+    fn scramble(source: &str, rng: &mut Rng) -> String {
+        let (tokens, _) = Lexer::with_trivia(source).lex();
+        let mut out = String::new();
+        for tk in tokens {
+            match tk.kind {
+                Tk::EOF => {}
+                Tk::Whitespace => out.push_str(rng.pick(&[" ", "  ", "\t", "   "])),
+                Tk::Newline => {
+                    out.push('\n');
+                    out.push_str(rng.pick(&["", " ", "\t\t", "      "]));
+                }
+                _ => {
+                    if rng.below(4) == 0 && !out.is_empty() && !out.ends_with('\n') {
+                        out.push(' ');
+                    }
+                    out.push_str(&source[tk.span.clone()]);
+                }
+            }
+        }
+        return out;
+    }
+
+    #[test]
+    fn agrees_with_the_printer_on_random_programs() {
+        let mut programs = Gen(Rng(0x9e3779b97f4a7c15));
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..300 {
+            let (tokens, _) = lex(&programs.program());
+            let (ast, errors) = parse(tokens);
+            assert!(errors.is_empty());
+
+            let printed = print(&ast);
+            assert_eq!(formatted(&printed), printed);
+
+            let messy = scramble(&printed, &mut rng);
+            assert_eq!(formatted(&messy), printed, "scrambled as:\n{messy}");
+        }
+    }
+}
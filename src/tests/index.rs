@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::index::{ NodeIndex, NodeKind },
+        lex,
+        parse,
+        parser::ast::Stmt,
+        tests::{ Gen, Rng },
+    };
+
+    fn parse_clean(source: &str) -> Vec<Stmt> {
+        let (tokens, _) = lex(source);
+        let (ast, errors) = parse(tokens);
+        assert!(errors.is_empty(), "unexpected errors in:\n{source}\n{errors:#?}");
+        return ast;
+    }
+
+    #[test]
+    fn innermost_node_at_offset() {
+        let source = "let x: int = a + (b * c)";
+        let ast = parse_clean(source);
+        let index = NodeIndex::build(&ast);
+
+        let b = index.node_at(source.find('b').unwrap()).unwrap();
+        assert_eq!(index.kind(b), Some(NodeKind::Expr));
+        assert_eq!(index.span(b), Some(&(18..19)));
+
+        // the product and its parentheses share a span, the sum encloses both
+        let product = index.parent(b).unwrap();
+        assert_eq!(index.span(product), Some(&(17..24)));
+        let ancestors: Vec<_> = index.ancestors(b).collect();
+        assert_eq!(ancestors.len(), 3);
+        assert_eq!(ancestors.last(), Some(&ast[0].uid));
+        assert_eq!(index.parent(ast[0].uid), None);
+
+        let ty = index.node_at(source.find("int").unwrap() + 1).unwrap();
+        assert_eq!(index.kind(ty), Some(NodeKind::Type));
+        assert_eq!(index.node_at(source.find(':').unwrap()), Some(ast[0].uid));
+        assert_eq!(index.node_at(source.len()), None);
+    }
+
+    #[test]
+    fn declarations_index_their_parts() {
+        let source = "class P{T} where T: Num\nx: T\nend\nenum E\nA(int)\nend\nfrom m import a end\nmatch v\ncase E.A(n) then\nend";
+        let ast = parse_clean(source);
+        let index = NodeIndex::build(&ast);
+
+        let kinds: Vec<NodeKind> = index.iter().map(|(_, entry)| entry.kind).collect();
+        for kind in [
+            NodeKind::TypeParam,
+            NodeKind::Constraint,
+            NodeKind::Field,
+            NodeKind::Variant,
+            NodeKind::ImportItem,
+            NodeKind::MatchArm,
+            NodeKind::Pattern,
+        ] {
+            assert!(kinds.contains(&kind), "no {kind:?} was indexed");
+        }
+
+        let field = index.node_at(source.find("x: T").unwrap()).unwrap();
+        assert_eq!(index.kind(field), Some(NodeKind::Field));
+        assert_eq!(index.parent(field), Some(ast[0].uid));
+    }
+
+    #[test]
+    fn every_node_sits_inside_its_parent() {
+        let mut programs = Gen(Rng(0x853c49e6748fea9b));
+        for _ in 0..200 {
+            let source = programs.program();
+            let ast = parse_clean(&source);
+            let index = NodeIndex::build(&ast);
+
+            for (id, entry) in index.iter() {
+                let Some(parent) = entry.parent else {
+                    assert_eq!(entry.depth, 0);
+                    continue;
+                };
+                let outer = index.get(parent).unwrap();
+                assert_eq!(outer.depth + 1, entry.depth);
+                assert!(
+                    outer.span.start <= entry.span.start && entry.span.end <= outer.span.end,
+                    "{id:?} at {:?} sticks out of {parent:?} at {:?} in:\n{source}",
+                    entry.span,
+                    outer.span
+                );
+            }
+        }
+    }
+}
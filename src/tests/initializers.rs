@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::{
+        analysis::{ index::NodeIndex, initializers },
+        emit::printer::print,
+        lex,
+        parse,
+        parser::ast::{ Stmt, StmtKind },
+    };
+
+    /// Parses and resolves `source`, giving back the tree and every error message
+    fn resolve(source: &str) -> (Vec<Stmt>, Vec<String>) {
+        let (tokens, _) = lex(source);
+        let (mut ast, mut errors) = parse(tokens);
+        errors.extend(initializers::resolve(&mut ast));
+        let errors = errors.iter().map(|error| format!("{error:?}")).collect();
+        return (ast, errors);
+    }
+
+    fn assert_error(source: &str, expected: &str) {
+        let (_, errors) = resolve(source);
+        assert!(
+            errors.iter().any(|error| error.contains(expected)),
+            "expected '{expected}' for:\n{source}\ngot {errors:#?}"
+        );
+    }
+
+    #[test]
+    fn constructors_initialize_fields() {
+        let source = "class Point\nx: int\ny: (float, str?)\nfunction Point(self, x)\nlet self.x = x\nlet self.y = default\nreturn self\nend\nend";
+        let (ast, errors) = resolve(source);
+        assert!(errors.is_empty(), "{errors:#?}");
+
+        let StmtKind::Class(decl) = &ast[0].kind else { panic!("expected a class") };
+        let StmtKind::Function { body, .. } = &decl.constructor.as_ref().unwrap().kind else {
+            panic!("expected a constructor");
+        };
+        assert!(matches!(&body[0].kind, StmtKind::FieldInit { name, .. } if name == "x"));
+        assert!(print(&ast).contains("let self.y = (0.0, None)"));
+    }
+
+    #[test]
+    fn default_takes_the_zero_value_of_the_annotation() {
+        let source = "let a: [int; 3] = default\nlet b: str = default\nlet c: bool{} = default\nlet d: (int, [bool; 2]) = default";
+        let (ast, errors) = resolve(source);
+        assert!(errors.is_empty(), "{errors:#?}");
+        assert_eq!(
+            print(&ast),
+            "let a: [int; 3] = [0; 3]\nlet b: str = \"\"\nlet c: bool{} = {}\nlet d: (int, [bool; 2]) = (0, [false; 2])\n"
+        );
+
+        // the new nodes get ids of their own and the top one keeps that of `default`
+        let StmtKind::Variable { value, .. } = &ast[3].kind else { panic!("expected a variable") };
+        let index = NodeIndex::build(&ast);
+        assert_eq!(index.parent(value.uid), Some(ast[3].uid));
+        let ids: HashSet<_> = index.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids.len(), index.iter().count());
+    }
+
+    #[test]
+    fn misplaced_initializers_are_reported() {
+        assert_error("let self.x = 1", "can only be used inside a constructor");
+        assert_error(
+            "class P\nx: int\nfunction P(self)\nend\nfunction set(self)\nlet self.x = 1\nend\nend",
+            "can only be used inside a constructor",
+        );
+        assert_error(
+            "class P\nx: int\nfunction P(self)\nlet f = function() let self.x = 1 end\nend\nend",
+            "can only be used inside a constructor",
+        );
+        assert_error("class P\nx: int\nfunction P(self)\nlet self.y = 1\nend\nend", "'y' is not a field");
+        assert_error("pub let self.y = 1", "cannot be used on field initializers");
+        assert_error("let self. = 1", "expected field name after 'self.'");
+    }
+
+    #[test]
+    fn misplaced_defaults_are_reported() {
+        assert_error("let a = default", "needs a type annotation");
+        assert_error("let a: int = default + 1", "can only be the value of an annotated 'let'");
+        assert_error("f(default)", "can only be the value of an annotated 'let'");
+        assert_error("let a: Point = default", "'Point' has no default value");
+        assert_error("let a: [int; n] = default", "must be a number");
+        assert_error("class P{T}\nx: T\nfunction P(self)\nlet self.x = default\nend\nend", "'T' has no default value");
+    }
+}
@@ -1 +1,148 @@
-pub mod parsing;
\ No newline at end of file
+pub mod emitting;
+pub mod formatting;
+pub mod index;
+pub mod initializers;
+pub mod parsing;
+pub mod printing;
+pub mod syntax;
+pub mod tracing;
+pub mod variants;
+pub mod visit;
+
+/// Tiny xorshift generator so the randomized tests are reproducible
+#[cfg(test)]
+struct Rng(u64);
+
+#[cfg(test)]
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        return self.0;
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        return (self.next() % (n as u64)) as usize;
+    }
+
+    /// Picks one of `items`
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        return items[self.below(items.len())];
+    }
+}
+
+/// Builds random but valid kaska programs out of every construct the parser knows
+#[cfg(test)]
+struct Gen(Rng);
+
+#[cfg(test)]
+impl Gen {
+    fn ty(&mut self) -> String {
+        return self.0.pick(&[
+            "int", "str?", "[int; 2]", "int{}", "(int, str)", "function(int) -> bool",
+            "Point{T}", "(function() -> int)?", "(bool,)",
+        ]).to_string();
+    }
+
+    fn atom(&mut self) -> String {
+        return self.0.pick(&[
+            "a", "b", "seq", "0", "42", "1.5", "\"s\\n$\"", "true", "false", "Shape.Empty", "int{}",
+        ]).to_string();
+    }
+
+    fn expr(&mut self, depth: usize) -> String {
+        if depth == 0 {
+            return self.atom();
+        }
+        let d = depth - 1;
+        return match self.0.below(16) {
+            0 | 1 => {
+                let op = self.0.pick(&[
+                    "+", "-", "*", "/", "**", "//", "%", "&&", "||", "&", "|", "<", "<=", ">",
+                    ">=", "==", "!=",
+                ]);
+                format!("{} {op} {}", self.expr(d), self.expr(d))
+            }
+            2 => format!("({})", self.expr(d)),
+            3 => format!("f({}, {})", self.expr(d), self.expr(d)),
+            4 => format!("({}).field", self.expr(d)),
+            5 => format!("seq[{}]", self.expr(d)),
+            6 => format!("seq[{}..]", self.expr(d)),
+            7 => format!("{} is {}", self.expr(d), self.ty()),
+            8 => format!("({} is not None)", self.atom()),
+            9 => format!("({}, {})", self.expr(d), self.expr(d)),
+            10 => format!("[{}; 3]", self.expr(d)),
+            11 => format!("{{{}, {}}}", self.expr(d), self.expr(d)),
+            12 => format!("function(y: int) return {} end", self.expr(d)),
+            13 => format!("{} |> g", self.expr(d)),
+            14 => format!("Shape.Circle({})", self.expr(d)),
+            _ => self.atom(),
+        };
+    }
+
+    fn iterable(&mut self, depth: usize) -> String {
+        return match self.0.below(3) {
+            0 => format!("{}..{}", self.expr(depth), self.expr(depth)),
+            1 => format!("0..={}", self.expr(depth)),
+            _ => self.expr(depth),
+        };
+    }
+
+    fn block(&mut self, depth: usize) -> String {
+        let mut block = String::new();
+        for _ in 0..self.0.below(3) {
+            block.push_str(&self.stmt(depth));
+            block.push('\n');
+        }
+        return block;
+    }
+
+    fn stmt(&mut self, depth: usize) -> String {
+        let d = depth.saturating_sub(1);
+        let choices = if depth == 0 { 5 } else { 10 };
+        return match self.0.below(choices) {
+            0 => format!("let x = {}", self.expr(depth)),
+            1 => format!("let (p, _): {} = {}", self.ty(), self.expr(depth)),
+            2 => format!("x {} {}", self.0.pick(&["=", "+=", "**="]), self.expr(depth)),
+            3 => format!("return {}", self.expr(depth)),
+            4 => self.expr(depth),
+            5 => {
+                format!(
+                    "if {} then\n{}else if {} then\n{}else\n{}end",
+                    self.expr(d),
+                    self.block(d),
+                    self.expr(d),
+                    self.block(d),
+                    self.block(d)
+                )
+            }
+            6 => format!("for i, s in {} do\n{}end", self.iterable(d), self.block(d)),
+            7 => {
+                format!(
+                    "match {}\ncase Shape.Circle(r) if {} then\n{}case 1 then\ncase (_, q) then\n{}end",
+                    self.expr(d),
+                    self.expr(d),
+                    self.block(d),
+                    self.block(d)
+                )
+            }
+            8 => format!("function g{{T}}(p: int, q) -> {} where T: Eq + Ord\n{}end", self.ty(), self.block(d)),
+            _ => format!("let l = function(z) -> int\n{}end", self.block(d)),
+        };
+    }
+
+    fn program(&mut self) -> String {
+        let mut source = String::from("enum Shape\n    Circle(float)\n    Empty\nend\n");
+        for _ in 0..self.0.below(4) {
+            let stmt = match self.0.below(6) {
+                0 => "pub class Point{T} where T: Num\nx: T\nfunction Point(self, x)\nlet self.x = default\nend\nstatic function zero()\nend\nend".to_string(),
+                1 => "from std.io import read, write as w end\nimport std.math as m".to_string(),
+                _ => self.stmt(3),
+            };
+            source.push_str(&stmt);
+            source.push('\n');
+        }
+        return source;
+    }
+}
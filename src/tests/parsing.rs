@@ -1,9 +1,692 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::{
+        common::{ errors::ErrorBuffer, meta::TypeDecl },
+        lex,
+        lexer::token::Tk,
+        parse,
+        parser::{
+            ast::{ Expr, ExprKind, Imported, Modifier, PatternKind, Stmt, StmtKind, TypeExpr, TypeKind },
+            parser::{ MAX_NESTING, Parser },
+        },
+        tests::Rng,
+    };
 
-    // #[test]
-    // fn it_adds_two() {
-    //     assert_eq!(2 + 2, 4);
-    // }
-}
\ No newline at end of file
+    fn parse_source(source: &str) -> (Vec<Stmt>, ErrorBuffer) {
+        let (tokens, _) = lex(source);
+        return parse(tokens);
+    }
+
+    /// Parses a single expression statement and hands back the expression
+    fn parse_expr(source: &str) -> Expr {
+        let (mut ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+        match ast.remove(0).kind {
+            StmtKind::Expression { expr } => expr,
+            other => panic!("expected an expression statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn malformed_statements_become_error_nodes() {
+        let source = "function f(x: , y: int) -> int\n    let a = )\n    let b = 2\nend\nlet c = 1\n";
+        let (ast, errors) = parse_source(source);
+
+        assert!(!errors.is_empty());
+        assert_eq!(ast.len(), 2);
+
+        let StmtKind::Function { params, body, .. } = &ast[0].kind else {
+            panic!("expected a function, got {:?}", ast[0].kind);
+        };
+        assert!(matches!(params[0].kind, ExprKind::Error));
+        assert!(matches!(params[1].kind, ExprKind::Parameter { .. }));
+        assert!(matches!(body[0].kind, StmtKind::Error));
+        assert!(matches!(body[1].kind, StmtKind::Variable { .. }));
+        assert!(matches!(ast[1].kind, StmtKind::Variable { .. }));
+    }
+
+    #[test]
+    fn broken_function_header_keeps_block_structure() {
+        let source = "function 5(x: int)\n    let a = 1\nend\nlet b = 2\n";
+        let (ast, errors) = parse_source(source);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(ast[0].kind, StmtKind::Error));
+        assert!(matches!(ast[1].kind, StmtKind::Variable { .. }));
+    }
+
+    #[test]
+    fn unterminated_input_does_not_hang() {
+        for source in ["f(a,", "f(", "function f(", "function f(x: int", "let x =", "end end"] {
+            let (_, errors) = parse_source(source);
+            assert!(!errors.is_empty(), "expected errors for {source:?}");
+        }
+
+        let (ast, errors) = Parser::new(vec![]).parse();
+        assert!(ast.is_empty() && errors.is_empty());
+    }
+
+    #[test]
+    fn postfix_chains_nest_left_to_right() {
+        let source = "sequence.sort().rev()";
+        let expr = parse_expr(source);
+        assert_eq!(expr.span, 0..source.len());
+
+        let ExprKind::Call { callee, args } = expr.kind else { panic!() };
+        assert!(args.is_empty());
+        let ExprKind::Field { object, name } = callee.kind else { panic!() };
+        assert_eq!(name, "rev");
+        assert_eq!(object.span, 0.."sequence.sort()".len());
+        let ExprKind::Call { callee, .. } = object.kind else { panic!() };
+        let ExprKind::Field { object, name } = callee.kind else { panic!() };
+        assert_eq!(name, "sort");
+        assert!(matches!(object.kind, ExprKind::Ident { .. }));
+    }
+
+    #[test]
+    fn postfix_index_and_type_arguments() {
+        let expr = parse_expr("self.items[i](x)");
+        let ExprKind::Call { callee, .. } = expr.kind else { panic!() };
+        let ExprKind::Index { object, index } = callee.kind else { panic!() };
+        assert_eq!(object.span, 0..10);
+        assert!(matches!(index.kind, ExprKind::Ident { .. }));
+
+        let source = "int{}.map(f)";
+        let expr = parse_expr(source);
+        assert_eq!(expr.span, 0..source.len());
+        let ExprKind::Call { callee, .. } = expr.kind else { panic!() };
+        let ExprKind::Field { object, .. } = callee.kind else { panic!() };
+        let ExprKind::TypeApply { args, .. } = object.kind else { panic!() };
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn class_members_and_constructor() {
+        let source = "class Point\n    x: int\n    y: int\n\n    function Point(self, x, y: int) -> Point\n        let z = x\n    end\n\n    function norm(self) -> float\n        self.x\n    end\nend\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        let StmtKind::Class(decl) = &ast[0].kind else { panic!("expected a class") };
+        assert_eq!(decl.name, "Point");
+        assert_eq!(decl.fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), ["x", "y"]);
+        assert_eq!(decl.methods.len(), 1);
+
+        let Some(ctor) = &decl.constructor else { panic!("expected a constructor") };
+        let StmtKind::Function { params, .. } = &ctor.kind else { panic!() };
+        assert!(matches!(&params[0].kind, ExprKind::Parameter { name, ty: None } if name == "self"));
+        assert!(matches!(&params[2].kind, ExprKind::Parameter { ty: Some(_), .. }));
+    }
+
+    #[test]
+    fn class_rejects_duplicate_members() {
+        let source = "class A\n    x: int\n    function x(self)\n    end\n    function A()\n    end\n    function A()\n    end\nend\n";
+        let (ast, errors) = parse_source(source);
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(ast[0].kind, StmtKind::Class(_)));
+    }
+
+    #[test]
+    fn enum_variants_and_construction() {
+        let source = "let s = Shape.Circle(1.5)\nlet e = Shape.Empty\nlet f = other.Circle(1)\n\nenum Shape\n    Circle(float)\n    Rect(float, float)\n    Empty\nend\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        let StmtKind::Enum(decl) = &ast[3].kind else { panic!("expected an enum") };
+        let payloads: Vec<_> = decl.variants.iter().map(|v| (v.name.as_str(), v.payload.len())).collect();
+        assert_eq!(payloads, [("Circle", 1), ("Rect", 2), ("Empty", 0)]);
+
+        // which names are enums is left to analysis, the parser only sees calls and fields
+        let StmtKind::Variable { value, .. } = &ast[0].kind else { panic!() };
+        assert!(matches!(&value.kind, ExprKind::Call { callee, args } if args.len() == 1 && matches!(callee.kind, ExprKind::Field { .. })));
+        let StmtKind::Variable { value, .. } = &ast[1].kind else { panic!() };
+        assert!(matches!(&value.kind, ExprKind::Field { .. }));
+    }
+
+    #[test]
+    fn enum_rejects_duplicate_variants() {
+        let (ast, errors) = parse_source("enum A\n    B\n    B(int)\nend\n");
+        assert_eq!(errors.len(), 1);
+        let StmtKind::Enum(decl) = &ast[0].kind else { panic!("expected an enum") };
+        assert_eq!(decl.variants.len(), 1);
+    }
+
+    #[test]
+    fn generic_functions_and_where_clauses() {
+        let source = "function scale(x: T) where (T: Integer) -> bool\nend\nfunction pair<A, B>(a: A, b: B) -> A where A: Eq + Hash, B: Eq\nend\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        // T is only mentioned in the where clause, which declares it
+        let StmtKind::Function { type_params, where_clause, ret, .. } = &ast[0].kind else { panic!() };
+        assert_eq!(type_params.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["T"]);
+        assert_eq!((where_clause[0].param.as_str(), where_clause[0].bound.as_str()), ("T", "Integer"));
+        assert!(ret.is_some());
+
+        let StmtKind::Function { type_params, where_clause, .. } = &ast[1].kind else { panic!() };
+        assert_eq!(type_params.len(), 2);
+        let bounds: Vec<_> = where_clause.iter().map(|c| (c.param.as_str(), c.bound.as_str())).collect();
+        assert_eq!(bounds, [("A", "Eq"), ("A", "Hash"), ("B", "Eq")]);
+    }
+
+    #[test]
+    fn generic_classes_accept_both_bracket_styles() {
+        for source in ["class Point<T> where T: Integer\n    x: T\nend\n", "class Point{T} where T: Integer\n    x: T\nend\n"] {
+            let (ast, errors) = parse_source(source);
+            assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+            let StmtKind::Class(decl) = &ast[0].kind else { panic!("expected a class") };
+            assert_eq!(decl.type_params.len(), 1);
+            assert_eq!(decl.where_clause.len(), 1);
+            assert_eq!(decl.fields.len(), 1);
+        }
+
+        let (_, errors) = parse_source("function f<T, T>()\nend\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    /// Parses the type annotation of a single `let` statement
+    fn parse_type(annotation: &str) -> TypeExpr {
+        let (mut ast, errors) = parse_source(&format!("let x: {annotation} = y"));
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+        match ast.remove(0).kind {
+            StmtKind::Variable { typ: Some(typ), .. } => typ,
+            other => panic!("expected an annotated variable, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn type_expressions() {
+        let ty = parse_type("int?");
+        assert_eq!(ty.span, 7..11);
+        let TypeKind::Nullable { inner } = ty.kind else { panic!() };
+        assert!(matches!(&inner.kind, TypeKind::Named { name } if name == "int"));
+
+        let TypeKind::Array { elem, size } = parse_type("[int; 3]").kind else { panic!() };
+        assert!(matches!(elem.kind, TypeKind::Named { .. }));
+        assert!(matches!(size.kind, ExprKind::Integer { value: 3 }));
+
+        let TypeKind::Sequence { elem } = parse_type("int{}").kind else { panic!() };
+        assert!(matches!(elem.kind, TypeKind::Named { .. }));
+
+        // both ways of applying type arguments mean the same thing
+        for annotation in ["Point{T}", "Point<T>"] {
+            let TypeKind::Generic { name, args } = parse_type(annotation).kind else { panic!() };
+            assert_eq!((name.as_str(), args.len()), ("Point", 1));
+        }
+
+        let TypeKind::Sequence { elem } = parse_type("Point{T}{}").kind else { panic!() };
+        assert!(matches!(elem.kind, TypeKind::Generic { .. }));
+
+        let TypeKind::Function { params, ret } = parse_type("function(int, str) -> bool?").kind else { panic!() };
+        assert_eq!(params.len(), 2);
+        assert!(matches!(ret.unwrap().kind, TypeKind::Nullable { .. }));
+
+        let TypeKind::Tuple { elems } = parse_type("(int, str{})").kind else { panic!() };
+        assert!(matches!(elems[1].kind, TypeKind::Sequence { .. }));
+
+        // parentheses on their own only group
+        let TypeKind::Nullable { inner } = parse_type("(function() -> int)?").kind else { panic!() };
+        assert!(matches!(inner.kind, TypeKind::Function { .. }));
+    }
+
+    #[test]
+    fn array_and_sequence_literals() {
+        let ExprKind::ArrayLiteral { elements } = parse_expr("[2, 2, 2]").kind else { panic!() };
+        assert_eq!(elements.len(), 3);
+
+        let ExprKind::ArrayLiteral { elements } = parse_expr("[\n    1,\n    2,\n]").kind else { panic!() };
+        assert_eq!(elements.len(), 2);
+
+        let ExprKind::ArrayLiteral { elements } = parse_expr("[]").kind else { panic!() };
+        assert!(elements.is_empty());
+
+        let source = "[[3; 5]; 10]";
+        let expr = parse_expr(source);
+        assert_eq!(expr.span, 0..source.len());
+        let ExprKind::ArrayRepeat { value, count } = expr.kind else { panic!() };
+        assert_eq!(value.span, 1..7);
+        assert!(matches!(value.kind, ExprKind::ArrayRepeat { .. }));
+        assert!(matches!(count.kind, ExprKind::Integer { value: 10 }));
+
+        let source = "{ 5, 10,\n  15, 20 }";
+        let expr = parse_expr(source);
+        assert_eq!(expr.span, 0..source.len());
+        let ExprKind::SeqLiteral { elements } = expr.kind else { panic!() };
+        assert_eq!(elements.len(), 4);
+    }
+
+    #[test]
+    fn lambdas_and_nested_functions() {
+        let source = "seq.map(function(x: int) return x + 1; end).collect()";
+        let expr = parse_expr(source);
+        assert_eq!(expr.span, 0..source.len());
+
+        let ExprKind::Call { callee, .. } = expr.kind else { panic!() };
+        let ExprKind::Field { object, .. } = callee.kind else { panic!() };
+        let ExprKind::Call { mut args, .. } = object.kind else { panic!() };
+        let ExprKind::Lambda { params, ret, body } = args.remove(0).kind else { panic!() };
+        assert_eq!(params.len(), 1);
+        assert!(ret.is_none());
+        assert!(matches!(body[0].kind, StmtKind::Return { value: Some(_) }));
+
+        let source = "function outer() -> int\n    function inner(x: int) -> int return x; end\n    let f = function(y: int) -> int\n        return y\n    end\n    return\nend\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        let StmtKind::Function { body, .. } = &ast[0].kind else { panic!() };
+        assert!(matches!(&body[0].kind, StmtKind::Function { name, .. } if name == "inner"));
+        assert!(matches!(&body[1].kind, StmtKind::Variable { value: Expr { kind: ExprKind::Lambda { .. }, .. }, .. }));
+        assert!(matches!(body[2].kind, StmtKind::Return { value: None }));
+    }
+
+    /// Renders an expression with every binary operation parenthesized so the
+    /// shape of the tree can be compared as a string
+    fn grouping(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Binary { lhs, rhs, op } => format!("({} {op} {})", grouping(lhs), grouping(rhs)),
+            ExprKind::Call { callee, args } => {
+                let args: Vec<_> = args.iter().map(grouping).collect();
+                format!("{}({})", grouping(callee), args.join(", "))
+            }
+            ExprKind::Field { object, name } => format!("{}.{name}", grouping(object)),
+            ExprKind::Ident { name } => name.clone(),
+            ExprKind::Integer { value } => value.to_string(),
+            other => format!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn binary_operator_precedence() {
+        let cases = [
+            ("a + b * c", "(a + (b * c))"),
+            ("a * b + c", "((a * b) + c)"),
+            ("a - b - c", "((a - b) - c)"),
+            ("a ** b ** c", "(a ** (b ** c))"),
+            ("(a + b) * c", "((a + b) * c)"),
+            ("x % 2 == 0 && y < 1 || z", "((((x % 2) == 0) && (y < 1)) || z)"),
+            ("a - b + c * d ** e ** f - g", "(((a - b) + (c * (d ** (e ** f)))) - g)"),
+            ("a * b ** c * d", "((a * (b ** c)) * d)"),
+            ("a || b && c | d & e", "(a || (b && (c | (d & e))))"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(grouping(&parse_expr(source)), expected, "for {source:?}");
+        }
+    }
+
+    #[test]
+    fn long_chains_count_as_nesting() {
+        let within = MAX_NESTING - 10;
+        let operands = vec!["a"; within];
+        let expr = parse_expr(&operands.join(" + "));
+        let ExprKind::Binary { rhs, .. } = &expr.kind else { panic!("expected '+' at the root") };
+        assert!(matches!(&rhs.kind, ExprKind::Ident { .. }));
+
+        let expr = parse_expr(&operands.join(" = "));
+        let ExprKind::Assignment { assignee, .. } = &expr.kind else { panic!("expected '=' at the root") };
+        assert!(matches!(&assignee.kind, ExprKind::Ident { .. }));
+
+        // folded chains are as deep as nested ones, however they are parsed
+        let deep = 3000;
+        let operands = vec!["a"; deep];
+        let sources = [
+            operands.join(" + "),
+            operands.join(" ** "),
+            operands.join(" = "),
+            operands.join(" |> "),
+            format!("a{}", ".f()".repeat(deep)),
+            format!("a{}", "[0]".repeat(deep)),
+            format!("if a then\n{}end\n", "else if a then\n".repeat(deep)),
+        ];
+        for source in sources {
+            let (_, errors) = parse_source(&source);
+            assert!(
+                format!("{errors:?}").contains("nested too deeply"),
+                "no nesting error for {}...: {errors:#?}",
+                &source[..20]
+            );
+        }
+    }
+
+    #[test]
+    fn deep_nesting_is_reported() {
+        let within = MAX_NESTING - 10;
+        let deep = MAX_NESTING * 5;
+        fn nest(open: &str, inner: &str, close: &str, depth: usize) -> String {
+            return format!("{}{inner}{}", open.repeat(depth), close.repeat(depth));
+        }
+
+        let cases: [fn(usize) -> String; 6] = [
+            |depth| nest("(", "1", ")", depth),
+            |depth| nest("f(", "1", ")", depth),
+            |depth| nest("x[", "1", "]", depth),
+            // each of these is an expression and a statement
+            |depth| nest("function() return ", "1", " end", depth / 2),
+            |depth| nest("if x then\n", "", "end\n", depth),
+            |depth| format!("let {}: {} = 1", nest("(", "a", ",)", depth), nest("(", "int", ",)", depth)),
+        ];
+
+        // the limit is meant for a main thread sized stack, test threads get less
+        let checks = move || {
+            for case in cases {
+                let source = case(within);
+                let (_, errors) = parse_source(&source);
+                assert!(errors.is_empty(), "unexpected errors for {source}: {errors:#?}");
+
+                let source = case(deep);
+                let (_, errors) = parse_source(&source);
+                assert!(
+                    format!("{errors:?}").contains("nested too deeply"),
+                    "no nesting error for {source}: {errors:#?}"
+                );
+            }
+        };
+        let thread = std::thread::Builder::new().stack_size(8 << 20).spawn(checks).unwrap();
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn pipes_desugar_into_calls() {
+        let cases = [
+            ("a |> f(b)", "f(a, b)"),
+            ("a |> f", "f(a)"),
+            ("a + b |> f() |> g(c)", "g(f((a + b)), c)"),
+            ("seq |> int.map(\n    inc\n)\n|> int.collect()", "int.collect(int.map(seq, inc))"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(grouping(&parse_expr(source)), expected, "for {source:?}");
+        }
+
+        let source = "x |> f(y)";
+        assert_eq!(parse_expr(source).span, 0..source.len());
+    }
+
+    #[test]
+    fn is_tests_bind_like_comparisons() {
+        let expr = parse_expr("a is None && b is not None");
+        let ExprKind::Binary { lhs, rhs, .. } = expr.kind else { panic!("expected '&&' at the root") };
+        assert!(matches!(lhs.kind, ExprKind::Is { negated: false, .. }));
+        let ExprKind::Is { ty, negated, .. } = rhs.kind else { panic!() };
+        assert!(negated);
+        assert!(matches!(&ty.kind, TypeKind::Named { name } if name == "None"));
+
+        let source = "x + 1 is int?";
+        let expr = parse_expr(source);
+        assert_eq!(expr.span, 0..source.len());
+        let ExprKind::Is { value, ty, .. } = expr.kind else { panic!() };
+        assert!(matches!(value.kind, ExprKind::Binary { .. }));
+        assert!(matches!(ty.kind, TypeKind::Nullable { .. }));
+
+        let ExprKind::Binary { lhs, .. } = parse_expr("a is int == true").kind else { panic!() };
+        assert!(matches!(lhs.kind, ExprKind::Is { .. }));
+
+        // '<' only opens type arguments when a '>' closes them
+        let ExprKind::Binary { lhs, rhs, .. } = parse_expr("a is Point < b").kind else { panic!() };
+        let ExprKind::Is { ty, .. } = lhs.kind else { panic!("expected 'is' on the left") };
+        assert!(matches!(&ty.kind, TypeKind::Named { name } if name == "Point"));
+        assert!(matches!(rhs.kind, ExprKind::Ident { .. }));
+        let ExprKind::Is { ty, .. } = parse_expr("a is Point<int>").kind else { panic!() };
+        assert!(matches!(&ty.kind, TypeKind::Generic { args, .. } if args.len() == 1));
+    }
+
+    #[test]
+    fn if_else_chains_share_one_end() {
+        let source = "if B is None then\n    print(1)\nelse if A then\n    print(2)\nelse\n    print(3)\nend\nif x then return 1 else return 2 end\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+        assert_eq!(ast.len(), 2);
+
+        let StmtKind::If { cond, then, otherwise } = &ast[0].kind else { panic!() };
+        assert!(matches!(cond.kind, ExprKind::Is { .. }));
+        assert_eq!(then.len(), 1);
+        let otherwise = otherwise.as_ref().unwrap();
+        let StmtKind::If { otherwise: Some(last), .. } = &otherwise[0].kind else { panic!() };
+        assert_eq!(last.len(), 1);
+
+        let StmtKind::If { then, otherwise: Some(otherwise), .. } = &ast[1].kind else { panic!() };
+        assert!(matches!(then[0].kind, StmtKind::Return { .. }));
+        assert!(matches!(otherwise[0].kind, StmtKind::Return { .. }));
+    }
+
+    #[test]
+    fn import_forms() {
+        let source = "from Module import Function end\nfrom Module import\n    thing1,\n    thing2 as t,\n    thing3,\nend\nimport std.io as m\nfrom Module import * end\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+        assert_eq!(ast.len(), 4);
+
+        let StmtKind::Import(decl) = &ast[0].kind else { panic!() };
+        let Imported::Items(items) = &decl.imported else { panic!() };
+        assert_eq!(items[0].name, "Function");
+
+        let StmtKind::Import(decl) = &ast[1].kind else { panic!() };
+        let Imported::Items(items) = &decl.imported else { panic!() };
+        let names: Vec<_> = items.iter().map(|i| (i.name.as_str(), i.alias.as_deref())).collect();
+        assert_eq!(names, [("thing1", None), ("thing2", Some("t")), ("thing3", None)]);
+        assert_eq!(&source[items[1].span.clone()], "thing2");
+
+        let StmtKind::Import(decl) = &ast[2].kind else { panic!() };
+        assert_eq!(decl.path, ["std", "io"]);
+        assert_eq!(&source[decl.path_span.clone()], "std.io");
+        assert_eq!(decl.alias.as_deref(), Some("m"));
+        assert!(matches!(decl.imported, Imported::Module));
+
+        let StmtKind::Import(decl) = &ast[3].kind else { panic!() };
+        assert!(matches!(decl.imported, Imported::Everything));
+    }
+
+    #[test]
+    fn declaration_modifiers() {
+        let source = "pub inline function f() end\npub static let x = 1\npub class A\n    static function make() end\nend\npub enum E A end\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+        assert_eq!(ast.len(), 4);
+
+        let StmtKind::Function { modifiers, .. } = &ast[0].kind else { panic!() };
+        assert_eq!(modifiers, &[Modifier::Pub, Modifier::Inline]);
+        assert_eq!(ast[0].span.start, 0);
+
+        let StmtKind::Variable { modifiers, .. } = &ast[1].kind else { panic!() };
+        assert_eq!(modifiers, &[Modifier::Pub, Modifier::Static]);
+
+        let StmtKind::Class(decl) = &ast[2].kind else { panic!() };
+        assert_eq!(decl.modifiers, [Modifier::Pub]);
+        let StmtKind::Function { modifiers, .. } = &decl.methods[0].kind else { panic!() };
+        assert_eq!(modifiers, &[Modifier::Static]);
+
+        let StmtKind::Enum(decl) = &ast[3].kind else { panic!() };
+        assert_eq!(decl.modifiers, [Modifier::Pub]);
+    }
+
+    #[test]
+    fn illegal_modifiers_are_reported() {
+        let cases = [
+            ("pub pub function f() end", "duplicate 'pub' modifier"),
+            ("inline class A end", "'inline' cannot be used on 'class' declarations"),
+            ("static enum E A end", "'static' cannot be used on 'enum' declarations"),
+            ("pub return 1", "expected a declaration after modifiers, got 'return'"),
+            ("function f()\n    pub let x = 1\nend", "modifiers are only allowed on top-level declarations"),
+        ];
+
+        for (source, message) in cases {
+            let (_, errors) = parse_source(source);
+            assert!(format!("{errors:?}").contains(message), "{source:?} should report {message:?}, got {errors:#?}");
+        }
+
+        // the declaration survives with only its legal modifiers
+        let (ast, _) = parse_source("inline pub class A end");
+        let StmtKind::Class(decl) = &ast[0].kind else { panic!() };
+        assert_eq!(decl.modifiers, [Modifier::Pub]);
+    }
+
+    #[test]
+    fn match_arms_and_patterns() {
+        let source = "match shape\n    case Shape.Circle(r) if r > 1.0 then\n        print(r)\n    case Shape.Rect(_, h) then print(h)\n    case 0 then\n    case None then\n    case other then\n        print(other)\n        return\nend\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        let StmtKind::Match { value, arms } = &ast[0].kind else { panic!("expected a match") };
+        assert!(matches!(&value.kind, ExprKind::Ident { name } if name == "shape"));
+        assert_eq!(arms.len(), 5);
+
+        let PatternKind::Variant { enum_name, variant, fields } = &arms[0].pattern.kind else { panic!() };
+        assert_eq!((enum_name.as_str(), variant.as_str()), ("Shape", "Circle"));
+        assert!(matches!(&fields[0].kind, PatternKind::Binding { name } if name == "r"));
+        assert!(arms[0].guard.is_some());
+        assert_eq!(&source[arms[0].span.clone()], "case Shape.Circle(r) if r > 1.0 then\n        print(r)");
+
+        let PatternKind::Variant { fields, .. } = &arms[1].pattern.kind else { panic!() };
+        assert!(matches!(fields[0].kind, PatternKind::Wildcard));
+        assert_eq!(arms[1].body.len(), 1);
+
+        assert!(matches!(arms[2].pattern.kind, PatternKind::Literal { .. }));
+        assert!(arms[2].body.is_empty());
+        assert!(matches!(arms[3].pattern.kind, PatternKind::None));
+        assert!(arms[4].is_catch_all());
+        assert_eq!(arms[4].body.len(), 2);
+    }
+
+    #[test]
+    fn broken_match_arms_recover() {
+        let source = "match x\n    let y = 1\n    case + then\n        print(1)\n    case 1 then\nend\nlet z = 2\n";
+        let (ast, errors) = parse_source(source);
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert_eq!(ast.len(), 2);
+
+        let StmtKind::Match { arms, .. } = &ast[0].kind else { panic!("expected a match") };
+        assert_eq!(arms.len(), 2);
+        assert!(matches!(arms[0].pattern.kind, PatternKind::Error));
+        assert_eq!(arms[0].body.len(), 1);
+    }
+
+    #[test]
+    fn broken_match_value_keeps_the_arms() {
+        let source = "match x +
+    case 1 then
+        print(1)
+    case _ then
+end
+let z = 2
+";
+        let (ast, errors) = parse_source(source);
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert_eq!(ast.len(), 2);
+
+        let StmtKind::Match { value, arms } = &ast[0].kind else { panic!("expected a match") };
+        assert!(matches!(value.kind, ExprKind::Error));
+        assert_eq!(arms.len(), 2);
+        assert_eq!(arms[0].body.len(), 1);
+    }
+
+    #[test]
+    fn missing_enum_variants_in_match() {
+        let source = "match s\n    case Shape.Circle(r) then\n    case Shape.Rect(1, h) then\n    case Shape.Empty if ok then\n    case Other.Rect(w, h) then\nend\n";
+        let (ast, _) = parse_source(source);
+        let StmtKind::Match { arms, .. } = &ast[0].kind else { panic!("expected a match") };
+
+        let decl = TypeDecl::Enum { variants: vec!["Circle".into(), "Rect".into(), "Empty".into()] };
+        assert_eq!(decl.missing_variants("Shape", arms), ["Rect", "Empty"]);
+        assert_eq!(decl.missing_variants("Shape", &arms[..0]).len(), 3);
+        // a variant of another enum with the same name covers nothing
+        assert_eq!(decl.missing_variants("Other", arms), ["Circle", "Empty"]);
+    }
+
+    #[test]
+    fn tuples_and_destructuring_let() {
+        let source = "let (x, _) = (1, f(a))\nlet ((a, b), c): ((int, int), str) = pair()\nlet one = (1,)\nlet unit = ()\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        let StmtKind::Variable { pattern, value, .. } = &ast[0].kind else { panic!() };
+        let PatternKind::Tuple { elements } = &pattern.kind else { panic!() };
+        assert!(matches!(&elements[0].kind, PatternKind::Binding { name } if name == "x"));
+        assert!(matches!(elements[1].kind, PatternKind::Wildcard));
+        assert!(matches!(&value.kind, ExprKind::Tuple { elements } if elements.len() == 2));
+        assert_eq!(ast[0].span.start, 0);
+
+        let StmtKind::Variable { pattern, typ, .. } = &ast[1].kind else { panic!() };
+        let PatternKind::Tuple { elements } = &pattern.kind else { panic!() };
+        assert!(matches!(elements[0].kind, PatternKind::Tuple { .. }));
+        assert!(matches!(&typ.as_ref().unwrap().kind, TypeKind::Tuple { elems } if elems.len() == 2));
+
+        let StmtKind::Variable { value, .. } = &ast[2].kind else { panic!() };
+        assert!(matches!(&value.kind, ExprKind::Tuple { elements } if elements.len() == 1));
+        let StmtKind::Variable { value, .. } = &ast[3].kind else { panic!() };
+        assert!(matches!(&value.kind, ExprKind::Tuple { elements } if elements.is_empty()));
+    }
+
+    #[test]
+    fn let_rejects_refutable_patterns() {
+        let (ast, errors) = parse_source("let (x, 1) = pair()\nlet None = y\n");
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(ast.iter().all(|stmt| matches!(stmt.kind, StmtKind::Variable { .. })));
+    }
+
+    #[test]
+    fn ranges_do_not_lex_as_floats() {
+        let (tokens, _) = lex("1..5 1.5..=2 x.0");
+        let kinds: Vec<_> = tokens.iter().map(|tk| tk.kind).collect();
+        assert_eq!(kinds, [
+            Tk::Integer, Tk::DotDot, Tk::Integer,
+            Tk::Float, Tk::DotDotEqual, Tk::Integer,
+            Tk::Ident, Tk::Dot, Tk::Integer,
+            Tk::EOF,
+        ]);
+    }
+
+    #[test]
+    fn ranges_in_for_loops_and_slices() {
+        let source = "for i in 1..len(seq) do\n    print(i)\nend\nfor i, s in pairs do end\nlet a = seq[1..]\nlet b = seq[..=n - 1]\nlet c = 0..n + 1\n";
+        let (ast, errors) = parse_source(source);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+
+        let StmtKind::For { binding, iterable, body } = &ast[0].kind else { panic!("expected a for loop") };
+        assert!(matches!(binding.kind, PatternKind::Binding { .. }));
+        assert!(matches!(&iterable.kind, ExprKind::Range { start: Some(_), end: Some(_), inclusive: false }));
+        assert_eq!(&source[iterable.span.clone()], "1..len(seq)");
+        assert_eq!(body.len(), 1);
+
+        let StmtKind::For { binding, .. } = &ast[1].kind else { panic!("expected a for loop") };
+        assert!(matches!(&binding.kind, PatternKind::Tuple { elements } if elements.len() == 2));
+
+        let StmtKind::Variable { value, .. } = &ast[2].kind else { panic!() };
+        let ExprKind::Index { index, .. } = &value.kind else { panic!() };
+        assert!(matches!(index.kind, ExprKind::Range { start: Some(_), end: None, .. }));
+
+        let StmtKind::Variable { value, .. } = &ast[3].kind else { panic!() };
+        let ExprKind::Index { index, .. } = &value.kind else { panic!() };
+        let ExprKind::Range { start: None, end: Some(end), inclusive: true } = &index.kind else { panic!() };
+        assert_eq!(grouping(end), "(n - 1)");
+
+        let StmtKind::Variable { value, .. } = &ast[4].kind else { panic!() };
+        let ExprKind::Range { end: Some(end), .. } = &value.kind else { panic!() };
+        assert_eq!(grouping(end), "(n + 1)");
+
+        // only slices may leave out a bound
+        let (_, errors) = parse_source("let d = 1..\nfor x in xs end\n");
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+    }
+
+    #[test]
+    fn parser_never_panics_on_random_input() {
+        const PIECES: &[&str] = &[
+            "let", "function", "return", "if", "then", "else", "for", "while", "class", "enum", "end",
+            "where", "is", "not", "import", "from", "as", "inline", "pub", "static", "in", "match", "case", "_", "do", "..", "..=", "x", "self",
+            "None", "0", "1.5", "\"s\"", "true", "(", ")", "[", "]", "{", "}", "+", "-", "*",
+            "/", "**", "=", "==", "+=", "<", ">", "!", "|", "|>", "&", "->", ":", ";", ",", ".",
+            "...", "?", "\n", "\n", " ", "#", "\\", "@", "\"",
+        ];
+
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        for _ in 0..2000 {
+            let mut source = String::new();
+            for _ in 0..rng.below(64) {
+                source.push_str(PIECES[rng.below(PIECES.len())]);
+                source.push(' ');
+            }
+
+            // if this returns at all the parser did its job
+            let _ = parse_source(&source);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::{ emit::printer::print, lex, parse, parser::ast::Stmt, tests::{ Gen, Rng } };
+
+    fn parse_clean(source: &str) -> Vec<Stmt> {
+        let (tokens, _) = lex(source);
+        let (ast, errors) = parse(tokens);
+        assert!(errors.is_empty(), "unexpected errors in:\n{source}\n{errors:#?}");
+        return ast;
+    }
+
+    /// The debug output of a tree with every span and uid blanked out, so two trees
+    /// parsed from differently laid out source can be compared
+    fn shape(ast: &[Stmt]) -> String {
+        let debug = format!("{ast:?}");
+        let mut out = String::with_capacity(debug.len());
+        let mut skipping = false;
+        for ch in debug.chars() {
+            if skipping && (ch.is_ascii_digit() || ch == '.') {
+                continue;
+            }
+            out.push(ch);
+            skipping = out.ends_with("span: ") || out.ends_with("uid: NodeId(");
+        }
+        return out;
+    }
+
+    /// Prints the tree parsed from `source` and checks that the output parses back
+    /// into the same tree and prints the same way again
+    fn round_trip(source: &str) -> String {
+        let ast = parse_clean(source);
+        let printed = print(&ast);
+
+        let reparsed = parse_clean(&printed);
+        assert_eq!(shape(&ast), shape(&reparsed), "tree changed for:\n{source}\nprinted as:\n{printed}");
+        assert_eq!(print(&reparsed), printed);
+        return printed;
+    }
+
+    #[test]
+    fn prints_canonical_source() {
+        let source = "pub   function add{T}(a: T,b: T)->T where T:Num+Eq\nreturn (a+b)*2\nend\nlet (x,_)=( 1 ,\"q\\\"\" )\nif x then print(x) else if y then\nelse z=1\nend\n";
+        let expected = "pub function add{T}(a: T, b: T) -> T where T: Num + Eq\n    return (a + b) * 2\nend\n\nlet (x, _) = (1, \"q\\\"\")\nif x then\n    print(x)\nelse if y then\nelse\n    z = 1\nend\n";
+        assert_eq!(round_trip(source), expected);
+    }
+
+    #[test]
+    fn parentheses_are_kept_only_where_needed() {
+        let cases = [
+            ("(a + b) * c", "(a + b) * c"),
+            ("a + (b * c)", "a + b * c"),
+            ("(a - b) - c", "a - b - c"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("(a ** b) ** c", "(a ** b) ** c"),
+            ("a ** (b ** c)", "a ** b ** c"),
+            ("(a || b) && c", "(a || b) && c"),
+            ("(a < b) is int", "a < b is int"),
+            ("a < (b is int)", "a < (b is int)"),
+            ("(a is int) < b", "(a is int) < b"),
+            ("(a + b).c(d)[0..n + 1]", "(a + b).c(d)[0..n + 1]"),
+            ("x |> f(1) |> g", "g(f(x, 1))"),
+            ("let t: (function() -> int)? = (1,)", "let t: (function() -> int)? = (1,)"),
+            ("x = 0.0000001 + 100000000000000000000.0", "x = 0.0000001 + 100000000000000000000.0"),
+            ("x = 1.50 * 000.25", "x = 1.5 * 0.25"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(round_trip(source), format!("{expected}\n"));
+        }
+    }
+
+    #[test]
+    fn random_programs_round_trip() {
+        let mut programs = Gen(Rng(0x2545f4914f6cdd1d));
+        for _ in 0..500 {
+            round_trip(&programs.program());
+        }
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::{
+        lexer::token::Tk,
+        parser::ast::{ ExprKind, StmtKind },
+        syntax::{ green::SyntaxKind, parse_file, tree::SyntaxElement },
+        tests::{ Gen, Rng },
+    };
+
+    #[test]
+    fn broken_source_is_kept_byte_for_byte() {
+        let sources = [
+            "let = 1 @ # comment\nfunction (\n  x = 1\n",
+            "match x\n  case then\nend end\n\t\r\n",
+            "let s = \"unterminated\n",
+            "# only a comment",
+            "",
+            "inlineé$",
+            "let s = \"héllo ✓\" # ünïcode\nπ = 3 ✓ 2\n",
+        ];
+        for source in sources {
+            let parsed = parse_file(source);
+            assert_eq!(parsed.root.text(), source);
+            assert_eq!(parsed.root.span(), 0..source.len());
+        }
+
+        let parsed = parse_file("x = 1 @ 2");
+        assert!(!parsed.errors.is_empty());
+        let unknown = parsed.root
+            .descendants()
+            .into_iter()
+            .flat_map(|node| node.tokens())
+            .find(|token| token.green.kind == Tk::Unknown)
+            .expect("the rejected character should be a token");
+        assert_eq!(unknown.span(), 6..7);
+
+        // characters outside of ASCII are cut whole
+        let parsed = parse_file("x = 1 ✓ 2");
+        let unknown = parsed.root
+            .descendants()
+            .into_iter()
+            .flat_map(|node| node.tokens())
+            .find(|token| token.green.kind == Tk::Unknown)
+            .expect("the rejected character should be a token");
+        assert_eq!(unknown.green.text, "✓");
+
+        let parsed = parse_file("let s = \"héllo ✓\"");
+        let StmtKind::Variable { value, .. } = &parsed.ast[0].kind else { panic!("expected a let") };
+        assert!(matches!(&value.kind, ExprKind::String { value } if value == "héllo ✓"));
+    }
+
+    #[test]
+    fn nodes_line_up_with_the_ast() {
+        let mut programs = Gen(Rng(0xda942042e4dd58b5));
+        for _ in 0..100 {
+            let source = programs.program().replace('\n', " # note\n");
+            let parsed = parse_file(&source);
+            assert!(parsed.errors.is_empty());
+            assert_eq!(parsed.root.text(), source);
+
+            for (id, entry) in parsed.index.iter() {
+                let node = parsed.syntax(id).unwrap_or_else(|| panic!("{id:?} is missing from the tree"));
+                assert_eq!(node.span(), entry.span, "{:?} in {source}", entry.kind);
+                assert_ne!(node.kind(), SyntaxKind::Root);
+            }
+        }
+    }
+
+    #[test]
+    fn nodes_are_kinds_of_constructs() {
+        let source = "let (x, _) = f(a)[0] + b.c # note\nif x then return end\n";
+        let parsed = parse_file(source);
+        assert!(parsed.errors.is_empty());
+
+        let kinds: Vec<SyntaxKind> = parsed.root.descendants().iter().map(|node| node.kind()).collect();
+        assert_eq!(
+            kinds,
+            [
+                SyntaxKind::Root,
+                SyntaxKind::VariableStmt,
+                SyntaxKind::TuplePattern,
+                SyntaxKind::BindingPattern,
+                SyntaxKind::WildcardPattern,
+                SyntaxKind::BinaryExpr,
+                SyntaxKind::IndexExpr,
+                SyntaxKind::CallExpr,
+                SyntaxKind::IdentExpr,
+                SyntaxKind::IdentExpr,
+                SyntaxKind::IntegerExpr,
+                SyntaxKind::FieldExpr,
+                SyntaxKind::IdentExpr,
+                SyntaxKind::IfStmt,
+                SyntaxKind::IdentExpr,
+                SyntaxKind::ReturnStmt,
+            ]
+        );
+
+        // modifiers are part of the declaration they are on
+        let parsed = parse_file("pub function f() end");
+        let function = &parsed.root.children()[0];
+        assert_eq!(function.kind(), SyntaxKind::FunctionStmt);
+        assert_eq!(function.text(), "pub function f() end");
+    }
+
+    #[test]
+    fn trivia_stays_outside_of_nodes() {
+        let source = "let x = a + b # sum\n".to_string();
+        let parsed = parse_file(&source);
+        let stmt = parsed.syntax(parsed.ast[0].uid).unwrap();
+        assert_eq!(stmt.text(), "let x = a + b");
+
+        let elements = parsed.root.children_with_tokens();
+        let kinds: Vec<Option<Tk>> = elements
+            .iter()
+            .map(|element| if let SyntaxElement::Token(token) = element { Some(token.green.kind) } else { None })
+            .collect();
+        assert_eq!(kinds, [None, Some(Tk::Whitespace), Some(Tk::Comment), Some(Tk::Newline)]);
+    }
+
+    #[test]
+    fn edits_leave_the_rest_of_the_file_alone() {
+        let source = "let total   = a +  b # keep  this\nprint(total)\n".to_string();
+        let parsed = parse_file(&source);
+
+        let b = parsed.root.covering(source.find('b').unwrap());
+        let call = parse_file("count(b)");
+        let replacement = call.syntax(call.ast[0].uid).unwrap().children()[0].green().clone();
+        let edited = b.replace_with(replacement);
+        assert_eq!(edited.text(), "let total   = a +  count(b) # keep  this\nprint(total)\n");
+
+        // the second statement was not touched, so it is still the same green node
+        let before = &parsed.root.children()[1];
+        let after = &edited.children()[1];
+        assert!(Rc::ptr_eq(before.green(), after.green()));
+    }
+
+    #[test]
+    fn covering_finds_the_innermost_node() {
+        let source = "let total   = a +  b # keep  this\nprint(total)\n".to_string();
+        let parsed = parse_file(&source);
+
+        let b = parsed.root.covering(source.find('b').unwrap());
+        assert_eq!(b.kind(), SyntaxKind::IdentExpr);
+        assert_eq!(b.text(), "b");
+        let sum = b.parent().unwrap();
+        assert_eq!(sum.kind(), SyntaxKind::BinaryExpr);
+        assert_eq!(sum.text(), "a +  b");
+
+        // trivia belongs to the closest node around it
+        let comment = parsed.root.covering(source.find('#').unwrap());
+        assert_eq!(comment.kind(), SyntaxKind::Root);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::{ cell::RefCell, io::Write, rc::Rc };
+    use crate::{ common::trace::Tracer, lexer::lexer::Lexer, parser::parser::Parser };
+
+    /// Trace output that stays readable after it is handed to the tracer
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            return self.0.borrow_mut().write(buf);
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    fn trace(source: &str) -> Vec<String> {
+        let out = Shared::default();
+        let tracer = Tracer::new(out.clone());
+
+        let source = source.to_string();
+        let mut lexer = Lexer::new(&source);
+        lexer.trace(tracer.clone());
+        let (tokens, _) = lexer.lex();
+        let mut parser = Parser::new(tokens);
+        parser.trace(tracer);
+        parser.parse();
+
+        let text = String::from_utf8(out.0.borrow().clone()).unwrap();
+        return text.lines().map(str::to_string).collect();
+    }
+
+    #[test]
+    fn rules_are_traced_with_positions_and_depth() {
+        let lines = trace("let x: int = (a)");
+        assert_eq!(lines[0], "token 0..3 Let \"let\"");
+        assert!(lines.contains(&"> stmt (depth 0) at 0..3 Let \"let\"".to_string()));
+        assert!(lines.contains(&"  > type (depth 1) at 7..10 Ident \"int\"".to_string()));
+        assert!(lines.contains(&"    < operand (depth 2) at 14..15 Ident \"a\" ok".to_string()));
+        assert_eq!(lines.last().unwrap(), "< stmt (depth 0) at 15..16 RParen \")\" ok");
+    }
+
+    #[test]
+    fn every_rule_entered_is_left() {
+        let lines = trace("class P\nx: int\nfunction P(self)\nlet self.x = (1, f(2\nend\nend\nlet y = ");
+        let entered = lines.iter().filter(|line| line.trim_start().starts_with('>')).count();
+        let left = lines.iter().filter(|line| line.trim_start().starts_with('<')).count();
+        assert_eq!(entered, left);
+        assert!(lines.iter().any(|line| line.ends_with("failed")));
+    }
+
+    #[test]
+    fn empty_or_zero_turns_tracing_off() {
+        for target in ["", "0"] {
+            assert!(Tracer::open(target).unwrap().is_none(), "tracing on for {target:?}");
+        }
+        assert!(!std::path::Path::new("0").exists());
+        assert!(Tracer::open("stderr").unwrap().is_some());
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::variants,
+        common::errors::ErrorBuffer,
+        emit::printer::print,
+        lex,
+        parse,
+        parser::ast::{ ExprKind, Stmt, StmtKind },
+    };
+
+    fn resolve(source: &str) -> (Vec<Stmt>, ErrorBuffer) {
+        let (tokens, _) = lex(source);
+        let (mut ast, mut errors) = parse(tokens);
+        errors.extend(variants::resolve(&mut ast));
+        return (ast, errors);
+    }
+
+    fn value_of(stmt: &Stmt) -> &ExprKind {
+        let StmtKind::Variable { value, .. } = &stmt.kind else { panic!("expected a variable") };
+        return &value.kind;
+    }
+
+    #[test]
+    fn enum_members_become_variants() {
+        let source = "let s = Shape.Circle(1.5)\nlet e = Shape.Empty\nlet f = other.Circle(1)\nlet a = Shape.Circle(2).area()\nenum Shape\n    Circle(float)\n    Empty\nend";
+        let (ast, errors) = resolve(source);
+        assert!(errors.is_empty(), "{errors:#?}");
+
+        // the enum is declared after its uses but is still recognized
+        assert!(matches!(value_of(&ast[0]), ExprKind::Variant { variant, args, .. } if variant == "Circle" && args.len() == 1));
+        assert!(matches!(value_of(&ast[1]), ExprKind::Variant { args, .. } if args.is_empty()));
+        assert!(matches!(value_of(&ast[2]), ExprKind::Call { .. }));
+        assert!(print(&ast).contains("let a = Shape.Circle(2).area()"));
+        let ExprKind::Call { callee, .. } = value_of(&ast[3]) else { panic!("expected a call") };
+        let ExprKind::Field { object, .. } = &callee.kind else { panic!("expected a method") };
+        assert!(matches!(object.kind, ExprKind::Variant { .. }));
+    }
+
+    #[test]
+    fn locals_shadow_enums() {
+        let source = "enum Shape\n    Circle(float)\nend\nfunction f(Shape)\nreturn Shape.Circle(1)\nend\nlet g = function() let Shape = s; return Shape.Circle end\nlet h = Shape.Circle(1)";
+        let (ast, errors) = resolve(source);
+        assert!(errors.is_empty(), "{errors:#?}");
+
+        let StmtKind::Function { body, .. } = &ast[1].kind else { panic!("expected a function") };
+        let StmtKind::Return { value: Some(value) } = &body[0].kind else { panic!("expected a return") };
+        assert!(matches!(value.kind, ExprKind::Call { .. }));
+        let ExprKind::Lambda { body, .. } = value_of(&ast[2]) else { panic!("expected a lambda") };
+        let StmtKind::Return { value: Some(value) } = &body[1].kind else { panic!("expected a return") };
+        assert!(matches!(value.kind, ExprKind::Field { .. }));
+
+        // the shadowing ends with the function
+        assert!(matches!(value_of(&ast[3]), ExprKind::Variant { .. }));
+    }
+
+    #[test]
+    fn unknown_variants_are_reported() {
+        let source = "enum Shape\n    Circle(float)\nend\nlet s = Shape.Square(1)\nmatch s\ncase Shape.Square(n) then\ncase Other.Square(n) then\nend";
+        let (_, errors) = resolve(source);
+        let errors = format!("{errors:?}");
+        assert_eq!(errors.matches("'Shape' has no variant 'Square'").count(), 2, "{errors}");
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lex,
+        parse,
+        parser::ast::{ Expr, ExprKind, Pattern, PatternKind, Stmt, TypeExpr, Visitor, VisitorMut },
+    };
+
+    fn parse_source(source: &str) -> Vec<Stmt> {
+        let (tokens, _) = lex(source);
+        let (ast, errors) = parse(tokens);
+        assert!(errors.is_empty(), "unexpected errors: {errors:#?}");
+        return ast;
+    }
+
+    /// Collects every name that is used or bound, in the order the walk reaches them
+    #[derive(Default)]
+    struct Names(Vec<String>);
+
+    impl Visitor for Names {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let ExprKind::Ident { name } = &expr.kind {
+                self.0.push(name.clone());
+            }
+            self.walk_expr(expr);
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern) {
+            if let PatternKind::Binding { name } = &pattern.kind {
+                self.0.push(name.clone());
+            }
+            self.walk_pattern(pattern);
+        }
+    }
+
+    #[test]
+    fn visitor_reaches_nested_nodes() {
+        let source = "class A\n    function m(self)\n        return f(x)[0]\n    end\nend\nlet (a, _) = (b, c)\nmatch v\n    case E.B(d) if g then h()\nend\nfor i in 0..n do\n    seq.push(function(y) return y end)\nend\n";
+
+        let mut names = Names::default();
+        names.visit_block(&parse_source(source));
+        assert_eq!(names.0, ["f", "x", "a", "b", "c", "v", "d", "g", "h", "i", "n", "seq", "y"]);
+    }
+
+    /// Renames every identifier and counts the types it passes on the way
+    struct Rename {
+        types: usize,
+    }
+
+    impl VisitorMut for Rename {
+        fn visit_expr(&mut self, expr: &mut Expr) {
+            if let ExprKind::Ident { name } = &mut expr.kind {
+                name.insert(0, '_');
+            }
+            self.walk_expr(expr);
+        }
+
+        fn visit_type(&mut self, ty: &mut TypeExpr) {
+            self.types += 1;
+            self.walk_type(ty);
+        }
+    }
+
+    #[test]
+    fn mutable_visitor_rewrites_in_place() {
+        let mut ast = parse_source("let x: (int, str?) = a + b\nfunction f(p: [int; n]) -> bool\n    return p\nend\n");
+
+        let mut rename = Rename { types: 0 };
+        rename.visit_block(&mut ast);
+        assert_eq!(rename.types, 7);
+
+        let mut names = Names::default();
+        names.visit_block(&ast);
+        assert_eq!(names.0, ["x", "_a", "_b", "_n", "_p"]);
+    }
+}